@@ -0,0 +1,28 @@
+use meme_common::MathError;
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InsufficientInputAmount = 3,
+    InsufficientLiquidity = 4,
+    LiquidityExceedsSupply = 5,
+    PoolNotFound = 6,
+    SlippageTooHigh = 7,
+    Overflow = 100,
+    DivisionByZero = 101,
+    NegativeValue = 102,
+}
+
+impl From<MathError> for Error {
+    fn from(err: MathError) -> Self {
+        match err {
+            MathError::Overflow => Error::Overflow,
+            MathError::DivisionByZero => Error::DivisionByZero,
+            MathError::NegativeValue => Error::NegativeValue,
+        }
+    }
+}
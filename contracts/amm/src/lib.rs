@@ -1,15 +1,22 @@
 #![no_std]
 
+mod errors;
+
+use meme_common::events;
+use meme_common::math::{add, mul, mul_div, sqrt_mul, Rounding, PRICE_SCALE};
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, Map, String,
+    contract, contractimpl, contracttype, symbol_short, token, Address, Env, Map, String,
 };
 
+pub use errors::Error;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct Pool {
     pub pool_id: u64,
     pub token_name: String,
     pub token_symbol: String,
+    pub token_address: Address,
     pub token_reserve: i128,
     pub xlm_reserve: i128,
     pub total_supply: i128,
@@ -18,39 +25,80 @@ pub struct Pool {
     pub lp_tokens: i128,
 }
 
+/// Preview of a swap. Prices are in stroops per whole token, the fee is in
+/// the input asset and the price impact is how far the swap moves the spot
+/// price.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Quote {
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub fee: i128,
+    pub avg_price: i128,
+    pub price_before: i128,
+    pub price_after: i128,
+    pub price_impact_bps: u32,
+}
+
 #[contract]
 pub struct AMM;
 
 #[contractimpl]
 impl AMM {
-    /// Simple square root approximation for LP tokens
-    fn sqrt(x: i128) -> i128 {
-        if x == 0 {
-            return 0;
+    /// Initialize the AMM with the native XLM Stellar Asset Contract
+    pub fn initialize(env: Env, xlm_token: Address) -> Result<(), Error> {
+        let xlm_key = symbol_short!("XLM");
+        if env.storage().instance().has(&xlm_key) {
+            return Err(Error::AlreadyInitialized);
         }
         
-        let mut z = (x + 1) / 2;
-        let mut y = x;
-        
-        while z < y {
-            y = z;
-            z = (x / z + z) / 2;
-        }
+        env.storage().instance().set(&xlm_key, &xlm_token);
         
-        y
+        Ok(())
+    }
+    
+    /// Get the native XLM token address used for settlement
+    pub fn get_xlm_token(env: Env) -> Result<Address, Error> {
+        Self::xlm_token(&env)
+    }
+    
+    /// Internal: Native XLM token address
+    fn xlm_token(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("XLM"))
+            .ok_or(Error::NotInitialized)
+    }
+    
+    /// Internal: Spot price of a pool, rounded down
+    fn price(env: &Env, pool: &Pool) -> Result<i128, Error> {
+        Ok(mul_div(env, pool.xlm_reserve, PRICE_SCALE, pool.token_reserve, Rounding::Down)?)
     }
     
     /// Create a new token pool with initial liquidity
+    ///
+    /// The creator deposits `token_amount` of `token_address` and
+    /// `initial_xlm` into the pool and owns the initial LP position.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_pool(
         env: Env,
         creator: Address,
+        token_address: Address,
         name: String,
         symbol: String,
         total_supply: i128,
+        token_amount: i128,
         initial_xlm: i128,
-    ) -> u64 {
+    ) -> Result<u64, Error> {
         creator.require_auth();
         
+        if token_amount <= 0 || initial_xlm <= 0 {
+            return Err(Error::InsufficientInputAmount);
+        }
+        if token_amount > total_supply {
+            return Err(Error::LiquidityExceedsSupply);
+        }
+        
         let pool_count_key = symbol_short!("POOL_CT");
         let pools_key = symbol_short!("POOLS");
         
@@ -61,17 +109,22 @@ impl AMM {
         
         let pool_id = pool_count + 1;
         
-        // Calculate initial reserves (50% of supply in pool)
-        let token_reserve = total_supply / 2;
+        let token_reserve = token_amount;
         let xlm_reserve = initial_xlm;
         
+        // Take custody of the initial liquidity
+        let pool_address = env.current_contract_address();
+        token::Client::new(&env, &token_address).transfer(&creator, &pool_address, &token_reserve);
+        token::Client::new(&env, &Self::xlm_token(&env)?).transfer(&creator, &pool_address, &xlm_reserve);
+        
         // Initial LP tokens = sqrt(token_reserve * xlm_reserve)
-        let lp_tokens = Self::sqrt(token_reserve * xlm_reserve);
+        let lp_tokens = sqrt_mul(&env, token_reserve, xlm_reserve)?;
         
         let pool = Pool {
             pool_id,
             token_name: name.clone(),
             token_symbol: symbol.clone(),
+            token_address,
             token_reserve,
             xlm_reserve,
             total_supply,
@@ -89,30 +142,30 @@ impl AMM {
         env.storage().instance().set(&pools_key, &pools);
         env.storage().instance().set(&pool_count_key, &pool_id);
         
-        env.events().publish((symbol_short!("CREATE"), symbol), pool_id);
+        env.events().publish((events::CREATE, symbol), pool_id);
         
-        pool_id
+        Ok(pool_id)
     }
     
     /// Calculate output amount for swap (constant product formula)
     fn get_amount_out(
+        env: &Env,
         amount_in: i128,
         reserve_in: i128,
         reserve_out: i128,
-    ) -> i128 {
+    ) -> Result<i128, Error> {
         if amount_in <= 0 {
-            panic!("Insufficient input amount");
+            return Err(Error::InsufficientInputAmount);
         }
         if reserve_in <= 0 || reserve_out <= 0 {
-            panic!("Insufficient liquidity");
+            return Err(Error::InsufficientLiquidity);
         }
         
         // Apply 0.3% fee
-        let amount_in_with_fee = amount_in * 997;
-        let numerator = amount_in_with_fee * reserve_out;
-        let denominator = (reserve_in * 1000) + amount_in_with_fee;
+        let amount_in_with_fee = mul(amount_in, 997)?;
+        let denominator = add(mul(reserve_in, 1000)?, amount_in_with_fee)?;
         
-        numerator / denominator
+        Ok(mul_div(env, amount_in_with_fee, reserve_out, denominator, Rounding::Down)?)
     }
     
     /// Swap XLM for tokens
@@ -122,41 +175,47 @@ impl AMM {
         pool_id: u64,
         xlm_amount: i128,
         min_tokens_out: i128,
-    ) -> i128 {
+    ) -> Result<i128, Error> {
         user.require_auth();
         
         let pools_key = symbol_short!("POOLS");
         let mut pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
         
-        let mut pool = pools.get(pool_id).unwrap();
+        let mut pool = pools.get(pool_id).ok_or(Error::PoolNotFound)?;
         
         // Calculate tokens out using AMM formula
         let tokens_out = Self::get_amount_out(
+            &env,
             xlm_amount,
             pool.xlm_reserve,
             pool.token_reserve,
-        );
+        )?;
         
         if tokens_out < min_tokens_out {
-            panic!("Slippage too high");
+            return Err(Error::SlippageTooHigh);
         }
         
+        // Settle: XLM in from user, tokens out of the pool
+        let pool_address = env.current_contract_address();
+        token::Client::new(&env, &Self::xlm_token(&env)?).transfer(&user, &pool_address, &xlm_amount);
+        token::Client::new(&env, &pool.token_address).transfer(&pool_address, &user, &tokens_out);
+        
         // Update reserves
-        pool.xlm_reserve += xlm_amount;
+        pool.xlm_reserve = add(pool.xlm_reserve, xlm_amount)?;
         pool.token_reserve -= tokens_out;
         
         pools.set(pool_id, pool.clone());
         env.storage().instance().set(&pools_key, &pools);
         
         env.events().publish(
-            (symbol_short!("SWAP"), pool.token_symbol),
+            (events::SWAP, pool.token_symbol),
             (user, xlm_amount, tokens_out),
         );
         
-        tokens_out
+        Ok(tokens_out)
     }
     
     /// Swap tokens for XLM
@@ -166,66 +225,72 @@ impl AMM {
         pool_id: u64,
         token_amount: i128,
         min_xlm_out: i128,
-    ) -> i128 {
+    ) -> Result<i128, Error> {
         user.require_auth();
         
         let pools_key = symbol_short!("POOLS");
         let mut pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
         
-        let mut pool = pools.get(pool_id).unwrap();
+        let mut pool = pools.get(pool_id).ok_or(Error::PoolNotFound)?;
         
         // Calculate XLM out using AMM formula
         let xlm_out = Self::get_amount_out(
+            &env,
             token_amount,
             pool.token_reserve,
             pool.xlm_reserve,
-        );
+        )?;
         
         if xlm_out < min_xlm_out {
-            panic!("Slippage too high");
+            return Err(Error::SlippageTooHigh);
         }
         
+        // Settle: tokens in from user, XLM out of the pool
+        let pool_address = env.current_contract_address();
+        token::Client::new(&env, &pool.token_address).transfer(&user, &pool_address, &token_amount);
+        token::Client::new(&env, &Self::xlm_token(&env)?).transfer(&pool_address, &user, &xlm_out);
+        
         // Update reserves
-        pool.token_reserve += token_amount;
+        pool.token_reserve = add(pool.token_reserve, token_amount)?;
         pool.xlm_reserve -= xlm_out;
         
         pools.set(pool_id, pool.clone());
         env.storage().instance().set(&pools_key, &pools);
         
         env.events().publish(
-            (symbol_short!("SWAP"), pool.token_symbol),
+            (events::SWAP, pool.token_symbol),
             (user, token_amount, xlm_out),
         );
         
-        xlm_out
+        Ok(xlm_out)
     }
     
     /// Calculate price (XLM per token)
-    pub fn get_price(env: Env, pool_id: u64) -> i128 {
+    pub fn get_price(env: Env, pool_id: u64) -> Result<i128, Error> {
         let pools_key = symbol_short!("POOLS");
         let pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
         
-        let pool = pools.get(pool_id).unwrap();
+        let pool = pools.get(pool_id).ok_or(Error::PoolNotFound)?;
         
         // Price = XLM reserve / Token reserve (normalized to 7 decimals)
-        (pool.xlm_reserve * 10_000_000) / pool.token_reserve
+        Self::price(&env, &pool)
     }
     
     /// Get pool info
-    pub fn get_pool(env: Env, pool_id: u64) -> Pool {
+    pub fn get_pool(env: Env, pool_id: u64) -> Result<Pool, Error> {
         let pools_key = symbol_short!("POOLS");
         let pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
         
-        pools.get(pool_id).unwrap()
+        pools.get(pool_id).ok_or(Error::PoolNotFound)
     }
     
     /// Get pool count
@@ -235,17 +300,17 @@ impl AMM {
     }
     
     /// Calculate market cap
-    pub fn get_market_cap(env: Env, pool_id: u64) -> i128 {
+    pub fn get_market_cap(env: Env, pool_id: u64) -> Result<i128, Error> {
         let pools_key = symbol_short!("POOLS");
         let pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
         
-        let pool = pools.get(pool_id).unwrap();
+        let pool = pools.get(pool_id).ok_or(Error::PoolNotFound)?;
         
-        let price = (pool.xlm_reserve * 10_000_000) / pool.token_reserve;
-        (price * pool.total_supply) / 10_000_000
+        let price = Self::price(&env, &pool)?;
+        Ok(mul_div(&env, price, pool.total_supply, PRICE_SCALE, Rounding::Down)?)
     }
     
     /// Quote swap (preview without executing)
@@ -253,16 +318,19 @@ impl AMM {
         env: Env,
         pool_id: u64,
         xlm_amount: i128,
-    ) -> i128 {
+    ) -> Result<Quote, Error> {
         let pools_key = symbol_short!("POOLS");
         let pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
+        
+        let pool = pools.get(pool_id).ok_or(Error::PoolNotFound)?;
         
-        let pool = pools.get(pool_id).unwrap();
+        let tokens_out =
+            Self::get_amount_out(&env, xlm_amount, pool.xlm_reserve, pool.token_reserve)?;
         
-        Self::get_amount_out(xlm_amount, pool.xlm_reserve, pool.token_reserve)
+        Self::quote(&env, &pool, xlm_amount, tokens_out, true)
     }
     
     /// Quote swap (preview without executing)
@@ -270,15 +338,128 @@ impl AMM {
         env: Env,
         pool_id: u64,
         token_amount: i128,
-    ) -> i128 {
+    ) -> Result<Quote, Error> {
         let pools_key = symbol_short!("POOLS");
         let pools: Map<u64, Pool> = env.storage()
             .instance()
             .get(&pools_key)
-            .unwrap();
+            .unwrap_or(Map::new(&env));
+        
+        let pool = pools.get(pool_id).ok_or(Error::PoolNotFound)?;
         
-        let pool = pools.get(pool_id).unwrap();
+        let xlm_out =
+            Self::get_amount_out(&env, token_amount, pool.token_reserve, pool.xlm_reserve)?;
         
-        Self::get_amount_out(token_amount, pool.token_reserve, pool.xlm_reserve)
+        Self::quote(&env, &pool, token_amount, xlm_out, false)
+    }
+    
+    /// Internal: Price a swap against the pool's current reserves
+    fn quote(
+        env: &Env,
+        pool: &Pool,
+        amount_in: i128,
+        amount_out: i128,
+        xlm_in: bool,
+    ) -> Result<Quote, Error> {
+        let mut after = pool.clone();
+        let (xlm_amount, token_amount) = if xlm_in {
+            after.xlm_reserve += amount_in;
+            after.token_reserve -= amount_out;
+            (amount_in, amount_out)
+        } else {
+            after.token_reserve += amount_in;
+            after.xlm_reserve -= amount_out;
+            (amount_out, amount_in)
+        };
+        
+        let avg_price = if token_amount > 0 {
+            mul_div(env, xlm_amount, PRICE_SCALE, token_amount, Rounding::Down)?
+        } else {
+            0
+        };
+        
+        let price_before = Self::price(env, pool)?;
+        let price_after = Self::price(env, &after)?;
+        
+        let price_impact_bps = if price_before > 0 {
+            let moved = (price_after - price_before).abs();
+            let bps = mul_div(env, moved, 10_000, price_before, Rounding::Up)?;
+            bps.min(u32::MAX as i128) as u32
+        } else {
+            0
+        };
+        
+        Ok(Quote {
+            amount_in,
+            amount_out,
+            fee: mul_div(env, amount_in, 3, 1000, Rounding::Up)?,
+            avg_price,
+            price_before,
+            price_after,
+            price_impact_bps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::{testutils::Address as _, token::StellarAssetClient, Env};
+
+    #[test]
+    fn test_pool_holds_liquidity_and_settles_swaps() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let contract_id = e.register_contract(None, AMM);
+        let client = AMMClient::new(&e, &contract_id);
+
+        let xlm = e
+            .register_stellar_asset_contract_v2(Address::generate(&e))
+            .address();
+        let meme = e
+            .register_stellar_asset_contract_v2(Address::generate(&e))
+            .address();
+        client.initialize(&xlm);
+
+        let creator = Address::generate(&e);
+        let user = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&creator, &1_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&creator, &1_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&user, &10_000);
+
+        let pool_id = client.create_pool(
+            &creator,
+            &meme,
+            &String::from_str(&e, "Doge"),
+            &String::from_str(&e, "DOGE"),
+            &1_000_000,
+            &1_000_000,
+            &1_000_000,
+        );
+        assert_eq!(token::Client::new(&e, &meme).balance(&contract_id), 1_000_000);
+        assert_eq!(token::Client::new(&e, &xlm).balance(&contract_id), 1_000_000);
+
+        let quote = client.quote_swap_xlm_to_tokens(&pool_id, &10_000);
+        assert_eq!(quote.fee, 30);
+        assert!(quote.price_impact_bps > 0);
+
+        assert_eq!(
+            client.try_swap_xlm_for_tokens(&user, &pool_id, &10_000, &(quote.amount_out + 1)),
+            Err(Ok(Error::SlippageTooHigh))
+        );
+        assert_eq!(client.try_get_pool(&(pool_id + 1)), Err(Ok(Error::PoolNotFound)));
+
+        let tokens_out = client.swap_xlm_for_tokens(&user, &pool_id, &10_000, &0);
+        assert_eq!(token::Client::new(&e, &meme).balance(&user), tokens_out);
+        assert_eq!(tokens_out, quote.amount_out);
+        assert_eq!(client.get_price(&pool_id), quote.price_after);
+
+        let xlm_out = client.swap_tokens_for_xlm(&user, &pool_id, &tokens_out, &0);
+        assert_eq!(token::Client::new(&e, &xlm).balance(&user), xlm_out);
+
+        let pool = client.get_pool(&pool_id);
+        assert_eq!(token::Client::new(&e, &xlm).balance(&contract_id), pool.xlm_reserve);
+        assert_eq!(token::Client::new(&e, &meme).balance(&contract_id), pool.token_reserve);
     }
 }
@@ -0,0 +1,86 @@
+use soroban_sdk::{contracttype, Env};
+
+use crate::storage::{get_candle, set_candle};
+
+/// Bucket width of an OHLCV candle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum CandleInterval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    OneDay,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [
+        CandleInterval::OneMinute,
+        CandleInterval::FiveMinutes,
+        CandleInterval::OneHour,
+        CandleInterval::OneDay,
+    ];
+
+    pub fn seconds(&self) -> u64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 300,
+            CandleInterval::OneHour => 3_600,
+            CandleInterval::OneDay => 86_400,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`
+    pub fn bucket(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.seconds()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Candle {
+    pub open_time: u64,
+    pub open: i128,
+    pub high: i128,
+    pub low: i128,
+    pub close: i128,
+    pub xlm_volume: i128,
+    pub token_volume: i128,
+    pub trades: u32,
+}
+
+/// Fold a trade into every candle interval of a token
+pub fn record(e: &Env, token_id: u64, price: i128, xlm_amount: i128, token_amount: i128) {
+    let timestamp = e.ledger().timestamp();
+
+    for interval in CandleInterval::ALL {
+        let open_time = interval.bucket(timestamp);
+
+        let candle = match get_candle(e, token_id, interval, open_time) {
+            Some(mut candle) => {
+                if price > candle.high {
+                    candle.high = price;
+                }
+                if price < candle.low {
+                    candle.low = price;
+                }
+                candle.close = price;
+                candle.xlm_volume += xlm_amount;
+                candle.token_volume += token_amount;
+                candle.trades += 1;
+                candle
+            }
+            None => Candle {
+                open_time,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                xlm_volume: xlm_amount,
+                token_volume: token_amount,
+                trades: 1,
+            },
+        };
+
+        set_candle(e, token_id, interval, &candle);
+    }
+}
@@ -0,0 +1,254 @@
+use meme_common::math::{add, mul, mul_div, sub, Rounding, PRICE_SCALE};
+use soroban_sdk::{contracttype, Env, Vec};
+
+use crate::errors::Error;
+
+const BPS: i128 = 10_000;
+const GROWTH_SCALE: i128 = 1_000_000_000_000;
+const MAX_STEPS: u32 = 32;
+
+/// Constant product `x * y = k` over virtual reserves
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ConstantProductCurve {
+    pub virtual_xlm: i128,
+    pub virtual_tokens: i128,
+}
+
+/// Price grows linearly: `base_price + slope * supply`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LinearCurve {
+    pub base_price: i128,
+    pub slope: i128,
+}
+
+/// Price grows by `growth_bps` every `step` tokens sold
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ExponentialCurve {
+    pub base_price: i128,
+    pub growth_bps: u32,
+    pub step: i128,
+}
+
+/// Fixed `price` until `current_supply` reaches `supply`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CurveStep {
+    pub supply: i128,
+    pub price: i128,
+}
+
+/// Price discovery model chosen by the creator at launch
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum CurveKind {
+    ConstantProduct(ConstantProductCurve),
+    Linear(LinearCurve),
+    Exponential(ExponentialCurve),
+    Stepped(Vec<CurveStep>),
+}
+
+impl CurveKind {
+    /// Fails if the curve parameters are unusable
+    pub fn validate(&self) -> Result<(), Error> {
+        let valid = match self {
+            CurveKind::ConstantProduct(c) => c.virtual_xlm > 0 && c.virtual_tokens > 0,
+            CurveKind::Linear(c) => c.base_price > 0 && c.slope >= 0,
+            CurveKind::Exponential(c) => c.base_price > 0 && c.growth_bps > 0 && c.step > 0,
+            CurveKind::Stepped(steps) => {
+                let mut valid = !steps.is_empty() && steps.len() <= MAX_STEPS;
+                let mut prev = 0;
+                for step in steps.iter() {
+                    valid = valid && step.supply > prev && step.price > 0;
+                    prev = step.supply;
+                }
+                valid
+            }
+        };
+
+        if !valid {
+            return Err(Error::InvalidCurve);
+        }
+        Ok(())
+    }
+
+    /// Fails if buys could not price the curve up to `total_supply`.
+    /// Constant product buys solve for the new supply directly, the other
+    /// curves search up to the reserve at max supply, which must not overflow.
+    pub fn validate_supply(&self, e: &Env, total_supply: i128) -> Result<(), Error> {
+        if let CurveKind::ConstantProduct(_) = self {
+            return Ok(());
+        }
+        self.reserve(e, self.max_supply(total_supply), Rounding::Up)
+            .map_err(|_| Error::InvalidCurve)?;
+        Ok(())
+    }
+
+    /// Spot price at `supply`, in stroops per whole token, rounded down
+    pub fn price(&self, e: &Env, supply: i128) -> Result<i128, Error> {
+        match self {
+            CurveKind::ConstantProduct(c) => {
+                let xlm_reserve = add(c.virtual_xlm, self.reserve(e, supply, Rounding::Down)?)?;
+                Ok(mul_div(
+                    e,
+                    xlm_reserve,
+                    PRICE_SCALE,
+                    c.virtual_tokens - supply,
+                    Rounding::Down,
+                )?)
+            }
+            CurveKind::Linear(c) => Ok(add(
+                c.base_price,
+                mul_div(e, c.slope, supply, PRICE_SCALE, Rounding::Down)?,
+            )?),
+            CurveKind::Exponential(c) => Self::grown_price(e, c, supply / c.step, Rounding::Down),
+            CurveKind::Stepped(steps) => {
+                let mut price = 0;
+                for step in steps.iter() {
+                    price = step.price;
+                    if supply < step.supply {
+                        break;
+                    }
+                }
+                Ok(price)
+            }
+        }
+    }
+
+    /// XLM the curve has taken in once `supply` tokens are sold
+    pub fn reserve(&self, e: &Env, supply: i128, rounding: Rounding) -> Result<i128, Error> {
+        match self {
+            CurveKind::ConstantProduct(c) => Ok(mul_div(
+                e,
+                c.virtual_xlm,
+                supply,
+                c.virtual_tokens - supply,
+                rounding,
+            )?),
+            CurveKind::Linear(c) => {
+                let base = mul_div(e, c.base_price, supply, PRICE_SCALE, rounding)?;
+                let half_slope = mul_div(e, c.slope, supply, 2 * PRICE_SCALE, rounding)?;
+                Ok(add(base, mul_div(e, half_slope, supply, PRICE_SCALE, rounding)?)?)
+            }
+            CurveKind::Exponential(c) => {
+                let n = supply / c.step;
+                let price_n = Self::grown_price(e, c, n, rounding)?;
+                // Geometric sum over the completed steps plus the partial one
+                let full = mul_div(
+                    e,
+                    c.step,
+                    sub(price_n, c.base_price)?,
+                    PRICE_SCALE,
+                    rounding,
+                )?;
+                let full = mul_div(e, full, BPS, c.growth_bps as i128, rounding)?;
+                let partial = mul_div(e, supply - n * c.step, price_n, PRICE_SCALE, rounding)?;
+                Ok(add(full, partial)?)
+            }
+            CurveKind::Stepped(steps) => {
+                let mut reserve = 0;
+                let mut start = 0;
+                for step in steps.iter() {
+                    if supply <= start {
+                        break;
+                    }
+                    let end = if supply < step.supply {
+                        supply
+                    } else {
+                        step.supply
+                    };
+                    let area = mul_div(e, end - start, step.price, PRICE_SCALE, rounding)?;
+                    reserve = add(reserve, area)?;
+                    start = step.supply;
+                }
+                Ok(reserve)
+            }
+        }
+    }
+
+    /// Highest supply the curve can be priced at, capped by `total_supply`
+    pub fn max_supply(&self, total_supply: i128) -> i128 {
+        let cap = match self {
+            CurveKind::ConstantProduct(c) => c.virtual_tokens - 1,
+            CurveKind::Stepped(steps) => steps.last().unwrap().supply,
+            _ => total_supply,
+        };
+
+        if cap < total_supply {
+            cap
+        } else {
+            total_supply
+        }
+    }
+
+    /// Largest supply whose reserve, rounded up, fits in `reserve` XLM,
+    /// searching upwards from `supply`. Fails if it would exceed `total_supply`.
+    pub fn supply_for_reserve(
+        &self,
+        e: &Env,
+        supply: i128,
+        reserve: i128,
+        total_supply: i128,
+    ) -> Result<i128, Error> {
+        let max_supply = self.max_supply(total_supply);
+
+        let new_supply = match self {
+            CurveKind::ConstantProduct(c) => mul_div(
+                e,
+                reserve,
+                c.virtual_tokens,
+                add(c.virtual_xlm, reserve)?,
+                Rounding::Down,
+            )?,
+            _ => {
+                if self.reserve(e, max_supply, Rounding::Up)? < reserve {
+                    return Err(Error::ExceedsAvailableSupply);
+                }
+
+                // Binary search for the largest supply whose reserve fits
+                let mut lo = supply;
+                let mut hi = max_supply;
+                while lo < hi {
+                    let mid = lo + (hi - lo + 1) / 2;
+                    if self.reserve(e, mid, Rounding::Up)? <= reserve {
+                        lo = mid;
+                    } else {
+                        hi = mid - 1;
+                    }
+                }
+                lo
+            }
+        };
+
+        if new_supply > max_supply {
+            return Err(Error::ExceedsAvailableSupply);
+        }
+
+        Ok(new_supply)
+    }
+
+    /// Internal: `base_price * (1 + growth_bps)^n`
+    fn grown_price(
+        e: &Env,
+        c: &ExponentialCurve,
+        n: i128,
+        rounding: Rounding,
+    ) -> Result<i128, Error> {
+        let mut factor = GROWTH_SCALE;
+        let mut base = GROWTH_SCALE + mul(GROWTH_SCALE, c.growth_bps as i128)? / BPS;
+        let mut exp = n;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                factor = mul_div(e, factor, base, GROWTH_SCALE, rounding)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = mul_div(e, base, base, GROWTH_SCALE, rounding)?;
+            }
+        }
+
+        Ok(mul_div(e, c.base_price, factor, GROWTH_SCALE, rounding)?)
+    }
+}
@@ -0,0 +1,48 @@
+use meme_common::MathError;
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    TokenNotFound = 3,
+    InvalidAmount = 4,
+    InvalidCurve = 5,
+    InvalidFeeConfig = 6,
+    InvalidGraduationConfig = 7,
+    InvalidLaunchRules = 8,
+    InvalidDevBuy = 9,
+    DeadlinePassed = 10,
+    SlippageTooHigh = 11,
+    ExceedsAvailableSupply = 12,
+    ExceedsCurrentSupply = 13,
+    InsufficientReserve = 14,
+    TokenGraduated = 15,
+    TradingPaused = 16,
+    RefundModeActive = 17,
+    RefundModeNotActive = 18,
+    NotOnAllowlist = 19,
+    BuyCooldownActive = 20,
+    MaxBuyExceeded = 21,
+    InsufficientPosition = 22,
+    NoFeesToClaim = 23,
+    NothingVested = 24,
+    TokenAdminNotCurve = 25,
+    TokenAlreadyLaunched = 26,
+    TokenAlreadyMinted = 27,
+    Overflow = 100,
+    DivisionByZero = 101,
+    NegativeValue = 102,
+}
+
+impl From<MathError> for Error {
+    fn from(err: MathError) -> Self {
+        match err {
+            MathError::Overflow => Error::Overflow,
+            MathError::DivisionByZero => Error::DivisionByZero,
+            MathError::NegativeValue => Error::NegativeValue,
+        }
+    }
+}
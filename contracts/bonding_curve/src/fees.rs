@@ -0,0 +1,47 @@
+use meme_common::math::{mul_div, Rounding};
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::errors::Error;
+
+pub const BPS: i128 = 10_000;
+
+/// Trading fees and how they are split. Whatever is not shared with the
+/// creator or referrer goes to the protocol treasury.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeConfig {
+    pub buy_fee_bps: u32,
+    pub sell_fee_bps: u32,
+    pub creator_share_bps: u32,
+    pub referrer_share_bps: u32,
+    pub treasury: Address,
+}
+
+impl FeeConfig {
+    /// Fails if the fee schedule is unusable
+    pub fn validate(&self) -> Result<(), Error> {
+        let valid = (self.buy_fee_bps as i128) < BPS
+            && (self.sell_fee_bps as i128) < BPS
+            && self.creator_share_bps as i128 + self.referrer_share_bps as i128 <= BPS;
+
+        if !valid {
+            return Err(Error::InvalidFeeConfig);
+        }
+        Ok(())
+    }
+}
+
+/// Fee of `fee_bps` charged on `amount`, rounded up
+pub fn fee_on(e: &Env, amount: i128, fee_bps: u32) -> Result<i128, Error> {
+    Ok(mul_div(e, amount, fee_bps as i128, BPS, Rounding::Up)?)
+}
+
+/// Smallest gross amount that leaves at least `net` after a `fee_bps` fee
+pub fn gross_for_net(e: &Env, net: i128, fee_bps: u32) -> Result<i128, Error> {
+    Ok(mul_div(e, net, BPS, BPS - fee_bps as i128, Rounding::Up)?)
+}
+
+/// Portion of `fee` owed for `share_bps`, rounded down
+pub fn share_of(e: &Env, fee: i128, share_bps: u32) -> Result<i128, Error> {
+    Ok(mul_div(e, fee, share_bps as i128, BPS, Rounding::Down)?)
+}
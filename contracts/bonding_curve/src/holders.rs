@@ -0,0 +1,111 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::errors::Error;
+use crate::storage::{
+    get_holder_count, get_position, get_top_holders, set_holder_count, set_position,
+    set_top_holders,
+};
+
+/// Size of the per-token top holders leaderboard
+pub const MAX_TOP_HOLDERS: u32 = 10;
+
+/// A trader's holdings of one launch bought through the curve
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Position {
+    pub balance: i128,
+    pub xlm_spent: i128,
+    pub xlm_received: i128,
+}
+
+/// Leaderboard entry
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Holder {
+    pub trader: Address,
+    pub balance: i128,
+}
+
+impl Position {
+    pub fn empty() -> Self {
+        Position {
+            balance: 0,
+            xlm_spent: 0,
+            xlm_received: 0,
+        }
+    }
+}
+
+/// Credit a buy to `trader`'s position
+pub fn record_buy(e: &Env, token_id: u64, trader: &Address, token_amount: i128, xlm_amount: i128) {
+    let mut position = get_position(e, token_id, trader).unwrap_or(Position::empty());
+    let was_holder = position.balance > 0;
+
+    position.balance += token_amount;
+    position.xlm_spent += xlm_amount;
+
+    update(e, token_id, trader, was_holder, &position);
+}
+
+/// Debit a sell from `trader`'s position. Fails if the trader never bought
+/// that many tokens through the curve.
+pub fn record_sell(
+    e: &Env,
+    token_id: u64,
+    trader: &Address,
+    token_amount: i128,
+    xlm_amount: i128,
+) -> Result<(), Error> {
+    let mut position = get_position(e, token_id, trader).unwrap_or(Position::empty());
+    if token_amount > position.balance {
+        return Err(Error::InsufficientPosition);
+    }
+
+    position.balance -= token_amount;
+    position.xlm_received += xlm_amount;
+
+    update(e, token_id, trader, true, &position);
+    Ok(())
+}
+
+/// Internal: Store the position and keep the holder count and leaderboard in
+/// step. Holders outside the leaderboard re-enter it when they next trade.
+fn update(e: &Env, token_id: u64, trader: &Address, was_holder: bool, position: &Position) {
+    set_position(e, token_id, trader, position);
+
+    let is_holder = position.balance > 0;
+    if is_holder != was_holder {
+        let count = get_holder_count(e, token_id);
+        set_holder_count(e, token_id, if is_holder { count + 1 } else { count - 1 });
+    }
+
+    let mut top = Vec::new(e);
+    for holder in get_top_holders(e, token_id).iter() {
+        if holder.trader != *trader {
+            top.push_back(holder);
+        }
+    }
+
+    if is_holder {
+        let mut index = top.len();
+        for (i, holder) in top.iter().enumerate() {
+            if position.balance > holder.balance {
+                index = i as u32;
+                break;
+            }
+        }
+        top.insert(
+            index,
+            Holder {
+                trader: trader.clone(),
+                balance: position.balance,
+            },
+        );
+    }
+
+    while top.len() > MAX_TOP_HOLDERS {
+        top.pop_back();
+    }
+
+    set_top_holders(e, token_id, &top);
+}
@@ -0,0 +1,82 @@
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::errors::Error;
+use crate::storage::{get_launch_rules, get_launch_wallet, is_allowlisted, set_launch_wallet};
+use crate::types::LaunchInfo;
+
+/// Optional anti-sniping rules set by the creator at launch. Windows are
+/// counted in ledgers from the launch ledger; a zero value disables a rule.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LaunchRules {
+    /// Most tokens one wallet may buy during the first `max_buy_ledgers`
+    pub max_buy_per_wallet: i128,
+    pub max_buy_ledgers: u32,
+    /// Ledgers a wallet must wait between two buys
+    pub buy_cooldown_ledgers: u32,
+    /// Only allowlisted wallets may buy during the first `allowlist_ledgers`
+    pub allowlist_ledgers: u32,
+}
+
+/// A wallet's buying activity on a protected launch
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LaunchWallet {
+    pub bought: i128,
+    pub last_buy_ledger: u32,
+}
+
+impl LaunchRules {
+    /// Fails if the rules are unusable
+    pub fn validate(&self) -> Result<(), Error> {
+        let capless = self.max_buy_ledgers > 0 && self.max_buy_per_wallet == 0;
+        if self.max_buy_per_wallet < 0 || capless {
+            return Err(Error::InvalidLaunchRules);
+        }
+        Ok(())
+    }
+}
+
+/// Fails if `buyer` may not buy `token_amount` more tokens of `token` in
+/// the current ledger, otherwise books the buy against the wallet
+pub fn enforce(
+    e: &Env,
+    token: &LaunchInfo,
+    buyer: &Address,
+    token_amount: i128,
+) -> Result<(), Error> {
+    let rules = match get_launch_rules(e, token.token_id) {
+        Some(rules) => rules,
+        None => return Ok(()),
+    };
+
+    let ledger = e.ledger().sequence();
+    let elapsed = ledger - token.launch_ledger;
+
+    if elapsed < rules.allowlist_ledgers && !is_allowlisted(e, token.token_id, buyer) {
+        return Err(Error::NotOnAllowlist);
+    }
+
+    let capped = elapsed < rules.max_buy_ledgers;
+    if !capped && rules.buy_cooldown_ledgers == 0 {
+        return Ok(());
+    }
+
+    let mut wallet = get_launch_wallet(e, token.token_id, buyer).unwrap_or(LaunchWallet {
+        bought: 0,
+        last_buy_ledger: 0,
+    });
+
+    if wallet.bought > 0 && ledger < wallet.last_buy_ledger + rules.buy_cooldown_ledgers {
+        return Err(Error::BuyCooldownActive);
+    }
+
+    wallet.bought += token_amount;
+    if capped && wallet.bought > rules.max_buy_per_wallet {
+        return Err(Error::MaxBuyExceeded);
+    }
+
+    wallet.last_buy_ledger = ledger;
+    set_launch_wallet(e, token.token_id, buyer, &wallet);
+    Ok(())
+}
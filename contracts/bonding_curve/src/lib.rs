@@ -1,325 +1,2119 @@
 #![no_std]
 
+mod candles;
+mod curve;
+mod errors;
+mod fees;
+mod holders;
+mod launch;
+mod ranking;
+mod storage;
+mod types;
+mod vesting;
+
+use meme_common::events;
+use meme_common::interfaces::AmmClient;
+use meme_common::math::{add, mul, mul_div, Rounding, PRICE_SCALE};
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, Map, String, Symbol,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contract, contractimpl, symbol_short, token, vec, Address, Env, IntoVal, Map, String, Symbol,
+    Vec,
 };
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[contracttype]
-pub struct TokenInfo {
-    pub token_id: u64,
-    pub name: String,
-    pub symbol: String,
-    pub total_supply: i128,
-    pub current_supply: i128,
-    pub virtual_xlm_reserve: i128,
-    pub virtual_token_reserve: i128,
-    pub creator: Address,
-    pub created_at: u64,
-}
+pub use candles::{Candle, CandleInterval};
+pub use curve::{ConstantProductCurve, CurveKind, CurveStep, ExponentialCurve, LinearCurve};
+pub use errors::Error;
+pub use fees::FeeConfig;
+pub use holders::{Holder, Position};
+pub use launch::{LaunchRules, LaunchWallet};
+pub use ranking::TrendingEntry;
+pub use types::{GraduationConfig, LaunchInfo, Quote, TradeHistory, TradeRef};
+pub use vesting::{DevBuy, Vesting};
+use storage::*;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[contracttype]
-pub struct TradeHistory {
-    pub trader: Address,
-    pub is_buy: bool,
-    pub token_amount: i128,
-    pub xlm_amount: i128,
-    pub price: i128,
-    pub timestamp: u64,
-}
+/// Upper bound on the number of entries a paginated query returns
+const MAX_PAGE_SIZE: u32 = 100;
 
 #[contract]
 pub struct BondingCurve;
 
 #[contractimpl]
 impl BondingCurve {
+    /// Initialize the curve with an admin, the native XLM Stellar Asset
+    /// Contract and the AMM that graduated launches migrate to
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        xlm_token: Address,
+        amm: Address,
+    ) -> Result<(), Error> {
+        if has_admin(&env) {
+            return Err(Error::AlreadyInitialized);
+        }
+        admin.require_auth();
+        
+        set_admin(&env, &admin);
+        set_xlm_token(&env, &xlm_token);
+        set_amm(&env, &amm);
+        set_graduation_config(
+            &env,
+            &GraduationConfig {
+                market_cap: 0,
+                supply_bps: 0,
+            },
+        );
+        set_fee_config(
+            &env,
+            &FeeConfig {
+                buy_fee_bps: 100,
+                sell_fee_bps: 100,
+                creator_share_bps: 0,
+                referrer_share_bps: 0,
+                treasury: admin.clone(),
+            },
+        );
+        
+        env.events().publish((events::INIT, admin), (xlm_token, amm));
+        
+        Ok(())
+    }
+    
+    /// Get the AMM contract graduated launches migrate to
+    pub fn get_amm(env: Env) -> Result<Address, Error> {
+        get_amm(&env)
+    }
+    
+    /// Get admin address
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        get_admin(&env)
+    }
+    
+    /// Hand the admin role to `new_admin` (admin only)
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        // The new admin must accept so the role can't go to a dead address
+        new_admin.require_auth();
+        
+        extend_instance(&env);
+        set_admin(&env, &new_admin);
+        
+        env.events().publish((events::ADMIN, admin), new_admin);
+        
+        Ok(())
+    }
+    
+    /// Halt or resume all launches and trading (admin only)
+    pub fn set_paused(env: Env, paused: bool) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        
+        extend_instance(&env);
+        set_paused(&env, paused);
+        
+        env.events().publish((events::PAUSE,), paused);
+        
+        Ok(())
+    }
+    
+    /// Check whether the whole curve is paused
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+    
+    /// Halt or resume trading of a single launch (admin only)
+    pub fn set_token_paused(env: Env, token_id: u64, paused: bool) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        
+        extend_instance(&env);
+        set_token_paused(&env, token_id, paused);
+        
+        env.events().publish((events::TOKEN_PAUSE, token_id), paused);
+        
+        Ok(())
+    }
+    
+    /// Check whether a launch is paused
+    pub fn is_token_paused(env: Env, token_id: u64) -> bool {
+        is_token_paused(&env, token_id)
+    }
+    
+    /// Put a launch into refund mode (admin only)
+    ///
+    /// Trading stops for good and holders can `refund` their tokens at the
+    /// XLM backing per token recorded now, which the reserve always covers.
+    pub fn enable_refund_mode(env: Env, token_id: u64) -> Result<i128, Error> {
+        get_admin(&env)?.require_auth();
+        
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        if token.pool_id.is_some() {
+            return Err(Error::TokenGraduated);
+        }
+        if get_refund_price(&env, token_id).is_some() {
+            return Err(Error::RefundModeActive);
+        }
+        
+        let price = if token.current_supply > 0 {
+            mul_div(&env, token.xlm_reserve, PRICE_SCALE, token.current_supply, Rounding::Down)?
+        } else {
+            0
+        };
+        
+        extend_instance(&env);
+        set_refund_price(&env, token_id, price);
+        
+        env.events().publish((events::REFUND_MODE, token_id), price);
+        
+        Ok(price)
+    }
+    
+    /// Get the fixed refund price of a launch in refund mode
+    pub fn get_refund_price(env: Env, token_id: u64) -> Option<i128> {
+        get_refund_price(&env, token_id)
+    }
+    
+    /// Sell tokens back at the recorded refund price, without fees. Works
+    /// while trading is paused.
+    pub fn refund(
+        env: Env,
+        seller: Address,
+        token_id: u64,
+        token_amount: i128,
+    ) -> Result<i128, Error> {
+        seller.require_auth();
+        
+        if token_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        extend_instance(&env);
+        let mut token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        let price = get_refund_price(&env, token_id).ok_or(Error::RefundModeNotActive)?;
+        
+        let xlm_out = mul_div(&env, token_amount, price, PRICE_SCALE, Rounding::Down)?;
+        holders::record_sell(&env, token_id, &seller, token_amount, xlm_out)?;
+        
+        // Settle: tokens back into curve custody, XLM out to seller
+        let curve = env.current_contract_address();
+        token::Client::new(&env, &token.token_address).transfer(&seller, &curve, &token_amount);
+        token::Client::new(&env, &get_xlm_token(&env)?).transfer(&curve, &seller, &xlm_out);
+        
+        token.current_supply -= token_amount;
+        token.xlm_reserve -= xlm_out;
+        set_token(&env, &token);
+        
+        env.events().publish(
+            (symbol_short!("REFUND"), token.symbol),
+            (seller, token_amount, xlm_out),
+        );
+        
+        Ok(xlm_out)
+    }
+    
+    /// Set graduation thresholds (admin only)
+    pub fn set_graduation_config(env: Env, config: GraduationConfig) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        
+        // Graduating at full supply would leave no tokens to seed the pool
+        if config.market_cap < 0 || config.supply_bps >= 10_000 {
+            return Err(Error::InvalidGraduationConfig);
+        }
+        
+        extend_instance(&env);
+        set_graduation_config(&env, &config);
+        
+        env.events().publish((events::GRADUATION_CONFIG,), config);
+        
+        Ok(())
+    }
+    
+    /// Get graduation thresholds
+    pub fn get_graduation_config(env: Env) -> Result<GraduationConfig, Error> {
+        get_graduation_config(&env)
+    }
+    
+    /// Set trading fees and their split (admin only)
+    pub fn set_fee_config(env: Env, config: FeeConfig) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        
+        config.validate()?;
+        
+        extend_instance(&env);
+        set_fee_config(&env, &config);
+        
+        env.events().publish((events::FEE_CONFIG,), config);
+        
+        Ok(())
+    }
+    
+    /// Get trading fees and their split
+    pub fn get_fee_config(env: Env) -> Result<FeeConfig, Error> {
+        get_fee_config(&env)
+    }
+    
+    /// Get XLM fees accrued to `recipient` and not yet claimed
+    pub fn get_accrued_fees(env: Env, recipient: Address) -> i128 {
+        get_accrued_fees(&env, &recipient)
+    }
+    
+    /// Claim all XLM fees accrued to `recipient`
+    pub fn claim_fees(env: Env, recipient: Address) -> Result<i128, Error> {
+        recipient.require_auth();
+        
+        extend_instance(&env);
+        let amount = get_accrued_fees(&env, &recipient);
+        if amount <= 0 {
+            return Err(Error::NoFeesToClaim);
+        }
+        
+        set_accrued_fees(&env, &recipient, 0);
+        
+        token::Client::new(&env, &get_xlm_token(&env)?).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        
+        env.events().publish((symbol_short!("CLAIM"), recipient), amount);
+        
+        Ok(amount)
+    }
+    
+    /// Get the native XLM token address used for settlement
+    pub fn get_xlm_token(env: Env) -> Result<Address, Error> {
+        get_xlm_token(&env)
+    }
+    
     /// Create a new token with bonding curve
+    ///
+    /// The curve mints `total_supply` of the SEP-41 `token_address` into its
+    /// own custody and sells it out along the curve. The creator must hand
+    /// the token's admin to the curve before minting any of it, and a token
+    /// can only be launched once. Optional
+    /// `launch_rules` throttle buys in the first ledgers after launch, and an
+    /// optional `dev_buy` buys the creator a vesting allocation first.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_token(
         env: Env,
         creator: Address,
+        token_address: Address,
         name: String,
         symbol: String,
         total_supply: i128,
-    ) -> u64 {
+        curve: CurveKind,
+        launch_rules: Option<LaunchRules>,
+        dev_buy: Option<DevBuy>,
+    ) -> Result<u64, Error> {
         creator.require_auth();
         
-        let token_count_key = symbol_short!("TOKEN_CT");
-        let tokens_key = symbol_short!("TOKENS");
+        if total_supply <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        curve.validate()?;
+        curve.validate_supply(&env, total_supply)?;
+        if let Some(rules) = &launch_rules {
+            rules.validate()?;
+        }
+        if let Some(dev_buy) = &dev_buy {
+            dev_buy.validate()?;
+        }
+        if is_paused(&env) {
+            return Err(Error::TradingPaused);
+        }
+        
+        if is_launched(&env, &token_address) {
+            return Err(Error::TokenAlreadyLaunched);
+        }
         
-        // Get current token count
-        let token_count: u64 = env.storage()
-            .instance()
-            .get(&token_count_key)
-            .unwrap_or(0);
+        // The creator must hand mint authority over to the curve
+        let curve_address = env.current_contract_address();
+        let asset = token::StellarAssetClient::new(&env, &token_address);
+        if !matches!(asset.try_admin(), Ok(Ok(admin)) if admin == curve_address) {
+            return Err(Error::TokenAdminNotCurve);
+        }
+        
+        // Nothing may have been minted before the handover. Plain SEP-41
+        // tokens don't expose their supply, so it is checked where they do.
+        let minted = env.try_invoke_contract::<i128, soroban_sdk::Error>(
+            &token_address,
+            &Symbol::new(&env, "total_supply"),
+            Vec::new(&env),
+        );
+        if matches!(minted, Ok(Ok(minted)) if minted != 0)
+            || token::Client::new(&env, &token_address).balance(&creator) != 0
+            || token::Client::new(&env, &token_address).balance(&curve_address) != 0
+        {
+            return Err(Error::TokenAlreadyMinted);
+        }
         
-        let token_id = token_count + 1;
+        extend_instance(&env);
+        
+        // Get current token count
+        let token_id = get_token_counter(&env) + 1;
         
-        // Initialize bonding curve reserves
-        // Starting with 1000 XLM virtual liquidity and 10% of supply
-        let virtual_xlm = 1_000_0000000i128; // 1000 XLM
-        let virtual_tokens = total_supply / 10; // 10% of total supply
+        // Mint the full supply straight into custody
+        asset.mint(&curve_address, &total_supply);
+        set_launched(&env, &token_address, token_id);
         
-        let token_info = TokenInfo {
+        let mut token_info = LaunchInfo {
             token_id,
             name: name.clone(),
             symbol: symbol.clone(),
+            token_address,
             total_supply,
             current_supply: 0,
-            virtual_xlm_reserve: virtual_xlm,
-            virtual_token_reserve: virtual_tokens,
+            xlm_reserve: 0,
+            curve,
             creator: creator.clone(),
             created_at: env.ledger().timestamp(),
+            launch_ledger: env.ledger().sequence(),
+            vesting: Vesting::none(),
+            pool_id: None,
         };
         
         // Store token
-        let mut tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap_or(Map::new(&env));
-        
-        tokens.set(token_id, token_info);
-        env.storage().instance().set(&tokens_key, &tokens);
-        env.storage().instance().set(&token_count_key, &token_id);
+        set_token(&env, &token_info);
+        set_token_counter(&env, token_id);
+        if let Some(rules) = &launch_rules {
+            set_launch_rules(&env, token_id, rules);
+        }
         
         // Log event
-        env.events().publish((symbol_short!("CREATE"), symbol), token_id);
+        env.events().publish((events::CREATE, symbol), token_id);
+        
+        // Creator's locked allocation, bought before anyone else can trade
+        if let Some(dev_buy) = &dev_buy {
+            Self::dev_buy(&env, &mut token_info, dev_buy)?;
+            set_token(&env, &token_info);
+        }
         
-        token_id
+        Ok(token_id)
     }
     
-    /// Calculate tokens received for XLM amount
-    pub fn calculate_buy(env: Env, token_id: u64, xlm_amount: i128) -> i128 {
-        let tokens_key = symbol_short!("TOKENS");
-        let tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
+    /// Claim the creator's dev-buy tokens vested so far
+    pub fn claim_vested(env: Env, token_id: u64) -> Result<i128, Error> {
+        let mut token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        token.creator.require_auth();
         
-        let token = tokens.get(token_id).unwrap();
+        extend_instance(&env);
+        let vested = token.vesting.vested(&env, env.ledger().timestamp())?;
+        let amount = vested - token.vesting.claimed;
+        if amount <= 0 {
+            return Err(Error::NothingVested);
+        }
+        
+        // The allocation only counts towards the creator's position once
+        // released, so locked tokens cannot be sold or refunded
+        let xlm_amount = token.vesting.cost_of(&env, token.vesting.claimed, vested)?;
+        holders::record_buy(&env, token_id, &token.creator, amount, xlm_amount);
+        
+        token.vesting.claimed = vested;
+        set_token(&env, &token);
+        
+        token::Client::new(&env, &token.token_address).transfer(
+            &env.current_contract_address(),
+            &token.creator,
+            &amount,
+        );
+        
+        env.events().publish(
+            (symbol_short!("VEST"), token.symbol),
+            (token.creator, amount),
+        );
         
-        // Constant product: X * Y = K
-        let k = token.virtual_xlm_reserve * token.virtual_token_reserve;
-        let new_xlm_reserve = token.virtual_xlm_reserve + xlm_amount;
-        let new_token_reserve = k / new_xlm_reserve;
-        let tokens_out = token.virtual_token_reserve - new_token_reserve;
+        Ok(amount)
+    }
+    
+    /// Quote tokens received for XLM amount
+    pub fn calculate_buy(env: Env, token_id: u64, xlm_amount: i128) -> Result<Quote, Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        let fee = fees::fee_on(&env, xlm_amount, get_fee_config(&env)?.buy_fee_bps)?;
         
-        tokens_out
+        let tokens_out = Self::tokens_out(&env, &token, xlm_amount - fee)?;
+        let new_supply = token.current_supply + tokens_out;
+        
+        Self::quote(&env, &token, xlm_amount, tokens_out, fee, new_supply, true)
     }
     
-    /// Calculate XLM received for token amount
-    pub fn calculate_sell(env: Env, token_id: u64, token_amount: i128) -> i128 {
-        let tokens_key = symbol_short!("TOKENS");
-        let tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
+    /// Quote XLM received for token amount
+    pub fn calculate_sell(env: Env, token_id: u64, token_amount: i128) -> Result<Quote, Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        
+        let xlm_out = Self::xlm_out(&env, &token, token_amount)?;
+        let fee = fees::fee_on(&env, xlm_out, get_fee_config(&env)?.sell_fee_bps)?;
+        let new_supply = token.current_supply - token_amount;
         
-        let token = tokens.get(token_id).unwrap();
+        Self::quote(&env, &token, token_amount, xlm_out - fee, fee, new_supply, false)
+    }
+    
+    /// Calculate XLM required to buy exactly `token_amount` tokens, including fees
+    pub fn calculate_buy_exact_tokens(
+        env: Env,
+        token_id: u64,
+        token_amount: i128,
+    ) -> Result<i128, Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        let net = Self::xlm_in(&env, &token, token_amount)?;
         
-        // Constant product: X * Y = K
-        let k = token.virtual_xlm_reserve * token.virtual_token_reserve;
-        let new_token_reserve = token.virtual_token_reserve + token_amount;
-        let new_xlm_reserve = k / new_token_reserve;
-        let xlm_out = token.virtual_xlm_reserve - new_xlm_reserve;
+        fees::gross_for_net(&env, net, get_fee_config(&env)?.buy_fee_bps)
+    }
+    
+    /// Calculate tokens required to receive exactly `xlm_amount` XLM after fees
+    pub fn calculate_sell_for_exact_xlm(
+        env: Env,
+        token_id: u64,
+        xlm_amount: i128,
+    ) -> Result<i128, Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        let gross = fees::gross_for_net(&env, xlm_amount, get_fee_config(&env)?.sell_fee_bps)?;
         
-        // Apply 1% trading fee
-        let fee = xlm_out / 100;
-        xlm_out - fee
+        Self::tokens_in(&env, &token, gross)
     }
     
     /// Buy tokens with XLM
-    pub fn buy(env: Env, buyer: Address, token_id: u64, xlm_amount: i128) -> i128 {
+    ///
+    /// Fails if fewer than `min_tokens_out` tokens would be received or the
+    /// ledger timestamp is past `deadline`.
+    pub fn buy(
+        env: Env,
+        buyer: Address,
+        token_id: u64,
+        xlm_amount: i128,
+        min_tokens_out: i128,
+        deadline: u64,
+        referrer: Option<Address>,
+    ) -> Result<i128, Error> {
         buyer.require_auth();
         
-        let tokens_key = symbol_short!("TOKENS");
-        let mut tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
+        if xlm_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        Self::check_deadline(&env, deadline)?;
+        
+        extend_instance(&env);
+        let mut token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
         
-        let mut token = tokens.get(token_id).unwrap();
+        Self::check_tradable(&env, &token)?;
         
-        // Calculate tokens out
-        let k = token.virtual_xlm_reserve * token.virtual_token_reserve;
-        let new_xlm_reserve = token.virtual_xlm_reserve + xlm_amount;
-        let new_token_reserve = k / new_xlm_reserve;
-        let tokens_out = token.virtual_token_reserve - new_token_reserve;
+        // Calculate tokens out for the XLM left after fees
+        let fee = fees::fee_on(&env, xlm_amount, get_fee_config(&env)?.buy_fee_bps)?;
+        let tokens_out = Self::tokens_out(&env, &token, xlm_amount - fee)?;
         
-        // Check if enough supply available
-        let max_available = token.total_supply - token.current_supply;
-        if tokens_out > max_available {
-            panic!("Exceeds available supply");
+        if tokens_out < min_tokens_out {
+            return Err(Error::SlippageTooHigh);
         }
         
-        // Update reserves and supply
-        token.virtual_xlm_reserve = new_xlm_reserve;
-        token.virtual_token_reserve = new_token_reserve;
-        token.current_supply += tokens_out;
+        Self::settle_buy(&env, &buyer, &mut token, xlm_amount, fee, tokens_out, &referrer)?;
         
         // Store updated token
-        tokens.set(token_id, token.clone());
-        env.storage().instance().set(&tokens_key, &tokens);
+        set_token(&env, &token);
         
-        // Record trade history
-        Self::record_trade(env.clone(), token_id, buyer.clone(), true, tokens_out, xlm_amount);
+        Ok(tokens_out)
+    }
+    
+    /// Buy exactly `token_amount` tokens
+    ///
+    /// Fails if more than `max_xlm_in` XLM would be spent or the ledger
+    /// timestamp is past `deadline`. Returns the XLM spent.
+    pub fn buy_exact_tokens(
+        env: Env,
+        buyer: Address,
+        token_id: u64,
+        token_amount: i128,
+        max_xlm_in: i128,
+        deadline: u64,
+        referrer: Option<Address>,
+    ) -> Result<i128, Error> {
+        buyer.require_auth();
         
-        // Emit event
-        env.events().publish(
-            (symbol_short!("BUY"), token.symbol),
-            (buyer, xlm_amount, tokens_out),
-        );
+        if token_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        Self::check_deadline(&env, deadline)?;
+        
+        extend_instance(&env);
+        let mut token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
         
-        tokens_out
+        Self::check_tradable(&env, &token)?;
+        
+        // Calculate XLM in, fees included
+        let net = Self::xlm_in(&env, &token, token_amount)?;
+        let xlm_amount = fees::gross_for_net(&env, net, get_fee_config(&env)?.buy_fee_bps)?;
+        let fee = xlm_amount - net;
+        
+        if xlm_amount > max_xlm_in {
+            return Err(Error::SlippageTooHigh);
+        }
+        
+        Self::settle_buy(&env, &buyer, &mut token, xlm_amount, fee, token_amount, &referrer)?;
+        
+        // Store updated token
+        set_token(&env, &token);
+        
+        Ok(xlm_amount)
     }
     
     /// Sell tokens for XLM
-    pub fn sell(env: Env, seller: Address, token_id: u64, token_amount: i128) -> i128 {
+    ///
+    /// Fails if less than `min_xlm_out` XLM would be received after fees or
+    /// the ledger timestamp is past `deadline`.
+    pub fn sell(
+        env: Env,
+        seller: Address,
+        token_id: u64,
+        token_amount: i128,
+        min_xlm_out: i128,
+        deadline: u64,
+        referrer: Option<Address>,
+    ) -> Result<i128, Error> {
         seller.require_auth();
         
-        let tokens_key = symbol_short!("TOKENS");
-        let mut tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
+        if token_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        Self::check_deadline(&env, deadline)?;
         
-        let mut token = tokens.get(token_id).unwrap();
+        extend_instance(&env);
+        let mut token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
         
-        // Verify seller has enough supply sold
-        if token_amount > token.current_supply {
-            panic!("Cannot sell more than current supply");
-        }
+        Self::check_tradable(&env, &token)?;
         
         // Calculate XLM out
-        let k = token.virtual_xlm_reserve * token.virtual_token_reserve;
-        let new_token_reserve = token.virtual_token_reserve + token_amount;
-        let new_xlm_reserve = k / new_token_reserve;
-        let xlm_out = token.virtual_xlm_reserve - new_xlm_reserve;
-        
-        // Apply 1% fee
-        let fee = xlm_out / 100;
+        let xlm_out = Self::xlm_out(&env, &token, token_amount)?;
+        let fee = fees::fee_on(&env, xlm_out, get_fee_config(&env)?.sell_fee_bps)?;
         let final_xlm = xlm_out - fee;
         
-        // Update reserves and supply
-        token.virtual_xlm_reserve = new_xlm_reserve;
-        token.virtual_token_reserve = new_token_reserve;
-        token.current_supply -= token_amount;
+        if final_xlm < min_xlm_out {
+            return Err(Error::SlippageTooHigh);
+        }
+        
+        Self::settle_sell(&env, &seller, &mut token, token_amount, final_xlm, fee, &referrer)?;
         
         // Store updated token
-        tokens.set(token_id, token.clone());
-        env.storage().instance().set(&tokens_key, &tokens);
+        set_token(&env, &token);
         
-        // Record trade history
-        Self::record_trade(env.clone(), token_id, seller.clone(), false, token_amount, final_xlm);
+        Ok(final_xlm)
+    }
+    
+    /// Sell tokens for exactly `xlm_amount` XLM after fees
+    ///
+    /// Fails if more than `max_tokens_in` tokens would be sold or the ledger
+    /// timestamp is past `deadline`. Returns the tokens sold.
+    pub fn sell_for_exact_xlm(
+        env: Env,
+        seller: Address,
+        token_id: u64,
+        xlm_amount: i128,
+        max_tokens_in: i128,
+        deadline: u64,
+        referrer: Option<Address>,
+    ) -> Result<i128, Error> {
+        seller.require_auth();
         
-        // Emit event
-        env.events().publish(
-            (symbol_short!("SELL"), token.symbol),
-            (seller, token_amount, final_xlm),
-        );
+        if xlm_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        Self::check_deadline(&env, deadline)?;
+        
+        extend_instance(&env);
+        let mut token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        
+        Self::check_tradable(&env, &token)?;
+        
+        // Calculate tokens in
+        let gross = fees::gross_for_net(&env, xlm_amount, get_fee_config(&env)?.sell_fee_bps)?;
+        let fee = gross - xlm_amount;
+        let token_amount = Self::tokens_in(&env, &token, gross)?;
+        
+        if token_amount > max_tokens_in {
+            return Err(Error::SlippageTooHigh);
+        }
+        
+        Self::settle_sell(&env, &seller, &mut token, token_amount, xlm_amount, fee, &referrer)?;
+        
+        // Store updated token
+        set_token(&env, &token);
         
-        final_xlm
+        Ok(token_amount)
     }
     
     /// Get current price per token
-    pub fn get_price(env: Env, token_id: u64) -> i128 {
-        let tokens_key = symbol_short!("TOKENS");
-        let tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
+    pub fn get_price(env: Env, token_id: u64) -> Result<i128, Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
         
-        let token = tokens.get(token_id).unwrap();
-        
-        // Price = XLM reserve / Token reserve (normalized to 7 decimals)
-        (token.virtual_xlm_reserve * 10_000_000) / token.virtual_token_reserve
+        Self::price(&env, &token)
     }
     
     /// Get market cap
-    pub fn get_market_cap(env: Env, token_id: u64) -> i128 {
-        let tokens_key = symbol_short!("TOKENS");
-        let tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
-        
-        let token = tokens.get(token_id).unwrap();
+    pub fn get_market_cap(env: Env, token_id: u64) -> Result<i128, Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
         
-        // Market cap = Current price * Current supply
-        let price = (token.virtual_xlm_reserve * 10_000_000) / token.virtual_token_reserve;
-        (price * token.current_supply) / 10_000_000
+        Self::market_cap(&env, &token)
     }
     
     /// Get token info
-    pub fn get_token_info(env: Env, token_id: u64) -> TokenInfo {
-        let tokens_key = symbol_short!("TOKENS");
-        let tokens: Map<u64, TokenInfo> = env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap();
+    pub fn get_token_info(env: Env, token_id: u64) -> Result<LaunchInfo, Error> {
+        get_token(&env, token_id).ok_or(Error::TokenNotFound)
+    }
+    
+    /// Allow or disallow wallets during a launch's allowlist phase (creator only)
+    pub fn set_allowlist(
+        env: Env,
+        token_id: u64,
+        wallets: Vec<Address>,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        let token = get_token(&env, token_id).ok_or(Error::TokenNotFound)?;
+        token.creator.require_auth();
+        
+        extend_instance(&env);
+        for wallet in wallets.iter() {
+            set_allowlisted(&env, token_id, &wallet, allowed);
+        }
         
-        tokens.get(token_id).unwrap()
+        Ok(())
+    }
+    
+    /// Get a launch's anti-sniping rules, if any
+    pub fn get_launch_rules(env: Env, token_id: u64) -> Option<LaunchRules> {
+        get_launch_rules(&env, token_id)
+    }
+    
+    /// Check whether `wallet` is on a launch's allowlist
+    pub fn is_allowlisted(env: Env, token_id: u64, wallet: Address) -> bool {
+        is_allowlisted(&env, token_id, &wallet)
     }
     
     /// Get total token count
     pub fn get_token_count(env: Env) -> u64 {
-        let token_count_key = symbol_short!("TOKEN_CT");
-        env.storage().instance().get(&token_count_key).unwrap_or(0)
+        get_token_counter(&env)
     }
     
     /// Get all tokens (for listing)
-    pub fn get_all_tokens(env: Env) -> Map<u64, TokenInfo> {
-        let tokens_key = symbol_short!("TOKENS");
-        env.storage()
-            .instance()
-            .get(&tokens_key)
-            .unwrap_or(Map::new(&env))
+    pub fn get_all_tokens(env: Env) -> Map<u64, LaunchInfo> {
+        let mut tokens = Map::new(&env);
+        
+        for token_id in 1..=get_token_counter(&env) {
+            if let Some(token) = get_token(&env, token_id) {
+                tokens.set(token_id, token);
+            }
+        }
+        
+        tokens
     }
     
-    /// Internal: Record trade history
-    fn record_trade(
-        env: Env,
-        token_id: u64,
-        trader: Address,
+    /// Internal: Reject trades on launches that graduated, are paused or are
+    /// being refunded
+    fn check_tradable(env: &Env, token: &LaunchInfo) -> Result<(), Error> {
+        if token.pool_id.is_some() {
+            return Err(Error::TokenGraduated);
+        }
+        if is_paused(env) || is_token_paused(env, token.token_id) {
+            return Err(Error::TradingPaused);
+        }
+        if get_refund_price(env, token.token_id).is_some() {
+            return Err(Error::RefundModeActive);
+        }
+        
+        Ok(())
+    }
+    
+    /// Internal: Reject trades submitted after their deadline
+    fn check_deadline(env: &Env, deadline: u64) -> Result<(), Error> {
+        if env.ledger().timestamp() > deadline {
+            return Err(Error::DeadlinePassed);
+        }
+        
+        Ok(())
+    }
+    
+    /// Internal: Spot price on the launch's curve (normalized to 7 decimals)
+    fn price(env: &Env, token: &LaunchInfo) -> Result<i128, Error> {
+        token.curve.price(env, token.current_supply)
+    }
+    
+    /// Internal: Market cap = Current price * Current supply
+    fn market_cap(env: &Env, token: &LaunchInfo) -> Result<i128, Error> {
+        let price = Self::price(env, token)?;
+        Ok(mul_div(env, price, token.current_supply, PRICE_SCALE, Rounding::Down)?)
+    }
+    
+    /// Internal: Price a trade that moves the supply to `new_supply`
+    fn quote(
+        env: &Env,
+        token: &LaunchInfo,
+        amount_in: i128,
+        amount_out: i128,
+        fee: i128,
+        new_supply: i128,
         is_buy: bool,
-        token_amount: i128,
-        xlm_amount: i128,
-    ) {
-        let history_key = Symbol::new(&env, "HISTORY");
-        let mut all_history: Map<u64, Map<u64, TradeHistory>> = env.storage()
-            .instance()
-            .get(&history_key)
-            .unwrap_or(Map::new(&env));
+    ) -> Result<Quote, Error> {
+        let (xlm_amount, token_amount) = if is_buy {
+            (amount_in, amount_out)
+        } else {
+            (amount_out, amount_in)
+        };
         
-        let mut token_history: Map<u64, TradeHistory> = all_history
-            .get(token_id)
-            .unwrap_or(Map::new(&env));
+        let avg_price = if token_amount > 0 {
+            mul_div(env, xlm_amount, PRICE_SCALE, token_amount, Rounding::Down)?
+        } else {
+            0
+        };
         
-        // Fix: Convert u32 to u64
-        let trade_count: u64 = token_history.len().into();
+        let price_before = Self::price(env, token)?;
+        let price_after = token.curve.price(env, new_supply)?;
         
-        let trade = TradeHistory {
-            trader,
-            is_buy,
-            token_amount,
-            xlm_amount,
-            price: (xlm_amount * 10_000_000) / token_amount,
-            timestamp: env.ledger().timestamp(),
+        let price_impact_bps = if price_before > 0 {
+            let moved = (price_after - price_before).abs();
+            let bps = mul_div(env, moved, 10_000, price_before, Rounding::Up)?;
+            bps.min(u32::MAX as i128) as u32
+        } else {
+            0
         };
         
-        token_history.set(trade_count, trade);
-        all_history.set(token_id, token_history);
-        env.storage().instance().set(&history_key, &all_history);
+        Ok(Quote {
+            amount_in,
+            amount_out,
+            fee,
+            avg_price,
+            price_before,
+            price_after,
+            price_impact_bps,
+        })
+    }
+    
+    /// Internal: Tokens bought by adding `xlm_amount` to the curve, rounded down
+    fn tokens_out(env: &Env, token: &LaunchInfo, xlm_amount: i128) -> Result<i128, Error> {
+        let curve = &token.curve;
+        let supply = token.current_supply;
+        
+        let reserve = curve.reserve(env, supply, Rounding::Down)?;
+        let reserve = add(reserve, xlm_amount)?;
+        let new_supply = curve.supply_for_reserve(env, supply, reserve, token.total_supply)?;
+        
+        Ok(new_supply - supply)
+    }
+    
+    /// Internal: XLM released by returning `token_amount` to the curve, before
+    /// fees, rounded down
+    fn xlm_out(env: &Env, token: &LaunchInfo, token_amount: i128) -> Result<i128, Error> {
+        if token_amount > token.current_supply {
+            return Err(Error::ExceedsCurrentSupply);
+        }
+        
+        let curve = &token.curve;
+        let supply = token.current_supply;
+        
+        let before = curve.reserve(env, supply, Rounding::Down)?;
+        let after = curve.reserve(env, supply - token_amount, Rounding::Up)?;
+        
+        if before > after {
+            Ok(before - after)
+        } else {
+            Ok(0)
+        }
+    }
+    
+    /// Internal: XLM needed to buy exactly `token_amount` more tokens, rounded up
+    fn xlm_in(env: &Env, token: &LaunchInfo, token_amount: i128) -> Result<i128, Error> {
+        let curve = &token.curve;
+        let supply = token.current_supply;
+        
+        let new_supply = supply + token_amount;
+        if new_supply > curve.max_supply(token.total_supply) {
+            return Err(Error::ExceedsAvailableSupply);
+        }
+        
+        let before = curve.reserve(env, supply, Rounding::Down)?;
+        let after = curve.reserve(env, new_supply, Rounding::Up)?;
+        
+        Ok(after - before)
+    }
+    
+    /// Internal: Tokens that must be returned to release `xlm_amount`, before
+    /// fees, rounded up
+    fn tokens_in(env: &Env, token: &LaunchInfo, xlm_amount: i128) -> Result<i128, Error> {
+        let curve = &token.curve;
+        let supply = token.current_supply;
+        
+        let reserve = curve.reserve(env, supply, Rounding::Down)? - xlm_amount;
+        if reserve < 0 {
+            return Err(Error::InsufficientReserve);
+        }
+        
+        Ok(supply - curve.supply_for_reserve(env, 0, reserve, supply)?)
     }
     
-    /// Get trade history for a token
-    pub fn get_trade_history(env: Env, token_id: u64) -> Map<u64, TradeHistory> {
-        let history_key = Symbol::new(&env, "HISTORY");
-        let all_history: Map<u64, Map<u64, TradeHistory>> = env.storage()
-            .instance()
-            .get(&history_key)
-            .unwrap_or(Map::new(&env));
+    /// Internal: Split a trading fee between creator, referrer and treasury
+    fn distribute_fee(
+        env: &Env,
+        token: &LaunchInfo,
+        fee: i128,
+        referrer: &Option<Address>,
+    ) -> Result<(), Error> {
+        if fee <= 0 {
+            return Ok(());
+        }
+        
+        let config = get_fee_config(env)?;
         
-        all_history.get(token_id).unwrap_or(Map::new(&env))
+        let credit = |recipient: &Address, amount: i128| {
+            if amount > 0 {
+                let balance = get_accrued_fees(env, recipient);
+                set_accrued_fees(env, recipient, balance + amount);
+            }
+        };
+        
+        let creator_share = fees::share_of(env, fee, config.creator_share_bps)?;
+        credit(&token.creator, creator_share);
+        
+        let referrer_share = match referrer {
+            Some(referrer) => {
+                let share = fees::share_of(env, fee, config.referrer_share_bps)?;
+                credit(referrer, share);
+                share
+            }
+            None => 0,
+        };
+        
+        credit(&config.treasury, fee - creator_share - referrer_share);
+        
+        Ok(())
+    }
+    
+    /// Internal: Move XLM in and tokens out for a buy and book it
+    fn settle_buy(
+        env: &Env,
+        buyer: &Address,
+        token: &mut LaunchInfo,
+        xlm_amount: i128,
+        fee: i128,
+        tokens_out: i128,
+        referrer: &Option<Address>,
+    ) -> Result<(), Error> {
+        // Anti-sniping rules of a fresh launch
+        launch::enforce(env, token, buyer, tokens_out)?;
+        holders::record_buy(env, token.token_id, buyer, tokens_out, xlm_amount);
+        
+        // Settle: XLM in from buyer, tokens out from curve custody
+        let curve = env.current_contract_address();
+        token::Client::new(env, &get_xlm_token(env)?).transfer(buyer, &curve, &xlm_amount);
+        token::Client::new(env, &token.token_address).transfer(&curve, buyer, &tokens_out);
+        
+        Self::book_buy(env, buyer, token, xlm_amount, fee, tokens_out, referrer)?;
+        
+        Ok(())
+    }
+    
+    /// Internal: Buy the creator's allocation at launch, kept in curve custody
+    /// until it vests
+    fn dev_buy(env: &Env, token: &mut LaunchInfo, dev_buy: &DevBuy) -> Result<(), Error> {
+        let creator = token.creator.clone();
+        let xlm_amount = dev_buy.xlm_amount;
+        let fee = fees::fee_on(env, xlm_amount, get_fee_config(env)?.buy_fee_bps)?;
+        let tokens_out = Self::tokens_out(env, token, xlm_amount - fee)?;
+        
+        let curve = env.current_contract_address();
+        token::Client::new(env, &get_xlm_token(env)?).transfer(&creator, &curve, &xlm_amount);
+        
+        token.vesting = dev_buy.schedule(tokens_out, env.ledger().timestamp())?;
+        Self::book_buy(env, &creator, token, xlm_amount, fee, tokens_out, &None)?;
+        
+        Ok(())
+    }
+    
+    /// Internal: Book a buy whose XLM has been received. The buyer's
+    /// position is credited by the caller.
+    fn book_buy(
+        env: &Env,
+        buyer: &Address,
+        token: &mut LaunchInfo,
+        xlm_amount: i128,
+        fee: i128,
+        tokens_out: i128,
+        referrer: &Option<Address>,
+    ) -> Result<(), Error> {
+        // Update supply and reserve, fees are kept aside for their recipients
+        token.current_supply += tokens_out;
+        token.xlm_reserve += xlm_amount - fee;
+        Self::distribute_fee(env, token, fee, referrer)?;
+        
+        // Record trade history
+        Self::record_trade(env.clone(), token.token_id, buyer.clone(), true, tokens_out, xlm_amount)?;
+        
+        // Emit event
+        env.events().publish(
+            (events::BUY, token.symbol.clone()),
+            (buyer.clone(), xlm_amount, tokens_out),
+        );
+        
+        // Migrate to the AMM once a threshold is reached
+        if Self::should_graduate(env, token)? {
+            Self::graduate(env, token)?;
+        }
+        
+        Self::rank(env, token)?;
+        
+        Ok(())
+    }
+    
+    /// Internal: Move tokens in and XLM out for a sell and book it
+    fn settle_sell(
+        env: &Env,
+        seller: &Address,
+        token: &mut LaunchInfo,
+        token_amount: i128,
+        final_xlm: i128,
+        fee: i128,
+        referrer: &Option<Address>,
+    ) -> Result<(), Error> {
+        if final_xlm + fee > token.xlm_reserve {
+            return Err(Error::InsufficientReserve);
+        }
+        holders::record_sell(env, token.token_id, seller, token_amount, final_xlm)?;
+        
+        // Settle: tokens back into curve custody, XLM out to seller
+        let curve = env.current_contract_address();
+        token::Client::new(env, &token.token_address).transfer(seller, &curve, &token_amount);
+        token::Client::new(env, &get_xlm_token(env)?).transfer(&curve, seller, &final_xlm);
+        
+        // Update supply and reserve, fees are kept aside for their recipients
+        token.current_supply -= token_amount;
+        token.xlm_reserve -= final_xlm + fee;
+        Self::distribute_fee(env, token, fee, referrer)?;
+        
+        // Record trade history
+        Self::record_trade(env.clone(), token.token_id, seller.clone(), false, token_amount, final_xlm)?;
+        
+        // Emit event
+        env.events().publish(
+            (events::SELL, token.symbol.clone()),
+            (seller.clone(), token_amount, final_xlm),
+        );
+        
+        Self::rank(env, token)?;
+        
+        Ok(())
+    }
+    
+    /// Internal: Progress toward graduation in basis points, capped at 100%.
+    /// Launches without graduation thresholds progress with supply sold.
+    fn progress_bps(env: &Env, token: &LaunchInfo) -> Result<u32, Error> {
+        let config = get_graduation_config(env)?;
+        let mut progress = 0;
+        
+        if config.market_cap > 0 {
+            let market_cap = Self::market_cap(env, token)?;
+            progress = mul_div(env, market_cap, 10_000, config.market_cap, Rounding::Down)?;
+        }
+        if config.supply_bps > 0 {
+            let target = mul(token.total_supply, config.supply_bps as i128)?;
+            let by_supply = mul_div(env, token.current_supply, 100_000_000, target, Rounding::Down)?;
+            progress = progress.max(by_supply);
+        }
+        if config.market_cap == 0 && config.supply_bps == 0 {
+            progress = mul_div(env, token.current_supply, 10_000, token.total_supply, Rounding::Down)?;
+        }
+        
+        Ok(progress.min(10_000) as u32)
+    }
+    
+    /// Internal: Refresh a launch's place on the trending leaderboard
+    fn rank(env: &Env, token: &LaunchInfo) -> Result<(), Error> {
+        let entry = if token.pool_id.is_some() {
+            None
+        } else {
+            Some(TrendingEntry {
+                token_id: token.token_id,
+                progress_bps: Self::progress_bps(env, token)?,
+                volume_24h: ranking::volume_24h(env, token.token_id),
+                last_trade: env.ledger().timestamp(),
+            })
+        };
+        
+        ranking::update(env, token.token_id, entry);
+        
+        Ok(())
+    }
+    
+    /// Internal: Whether a launch has reached a graduation threshold
+    fn should_graduate(env: &Env, token: &LaunchInfo) -> Result<bool, Error> {
+        let config = get_graduation_config(env)?;
+        
+        let by_market_cap = config.market_cap > 0 && Self::market_cap(env, token)? >= config.market_cap;
+        let by_supply = config.supply_bps > 0
+            && mul(token.current_supply, 10_000)?
+                >= mul(token.total_supply, config.supply_bps as i128)?;
+        
+        Ok(by_market_cap || by_supply)
+    }
+    
+    /// Internal: Close the curve and seed an AMM pool with the accumulated
+    /// XLM and enough unsold tokens to open at the final curve price. The
+    /// rest of the unsold tokens are burned. The LP position is owned by this
+    /// contract, which has no way to withdraw it, so the liquidity is locked
+    /// for good.
+    fn graduate(env: &Env, token: &mut LaunchInfo) -> Result<(), Error> {
+        let curve = env.current_contract_address();
+        let amm = get_amm(env)?;
+        let xlm = get_xlm_token(env)?;
+        
+        let xlm_amount = token.xlm_reserve;
+        let unsold = token.total_supply - token.current_supply;
+        // A sold-out curve has nothing left to pair with its XLM, so the buy
+        // that would sell it out is refused instead
+        if unsold <= 0 {
+            return Err(Error::ExceedsAvailableSupply);
+        }
+        let price = Self::price(env, token)?;
+        let at_price = mul_div(env, xlm_amount, PRICE_SCALE, price, Rounding::Down)?;
+        let token_amount = at_price.clamp(1, unsold);
+        
+        let burned = unsold - token_amount;
+        if burned > 0 {
+            token::Client::new(env, &token.token_address).burn(&curve, &burned);
+            token.total_supply -= burned;
+        }
+        
+        // The AMM pulls the liquidity from the curve
+        env.authorize_as_current_contract(vec![
+            env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: token.token_address.clone(),
+                    fn_name: symbol_short!("transfer"),
+                    args: (curve.clone(), amm.clone(), token_amount).into_val(env),
+                },
+                sub_invocations: vec![env],
+            }),
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: xlm,
+                    fn_name: symbol_short!("transfer"),
+                    args: (curve.clone(), amm.clone(), xlm_amount).into_val(env),
+                },
+                sub_invocations: vec![env],
+            }),
+        ]);
+        
+        let pool_id = AmmClient::new(env, &amm).create_pool(
+            &curve,
+            &token.token_address,
+            &token.name,
+            &token.symbol,
+            &token.total_supply,
+            &token_amount,
+            &xlm_amount,
+        );
+        
+        token.xlm_reserve = 0;
+        token.pool_id = Some(pool_id);
+        
+        env.events().publish(
+            (events::GRADUATE, token.symbol.clone()),
+            (token.token_id, pool_id, xlm_amount, token_amount, burned),
+        );
+        
+        Ok(())
+    }
+    
+    /// Internal: Record trade history
+    fn record_trade(
+        env: Env,
+        token_id: u64,
+        trader: Address,
+        is_buy: bool,
+        token_amount: i128,
+        xlm_amount: i128,
+    ) -> Result<(), Error> {
+        let trade_id = get_trade_counter(&env, token_id);
+        let price = mul_div(&env, xlm_amount, PRICE_SCALE, token_amount, Rounding::Down)?;
+        
+        let trade = TradeHistory {
+            token_id,
+            trade_id,
+            trader: trader.clone(),
+            is_buy,
+            token_amount,
+            xlm_amount,
+            price,
+            timestamp: env.ledger().timestamp(),
+        };
+        
+        set_trade(&env, token_id, trade_id, &trade);
+        set_trade_counter(&env, token_id, trade_id + 1);
+        add_trader_trade(&env, &trader, &TradeRef { token_id, trade_id });
+        candles::record(&env, token_id, price, xlm_amount, token_amount);
+        
+        Ok(())
+    }
+    
+    /// Get `interval` candles for a token whose buckets open between `from`
+    /// and `to` (inclusive). Buckets without trades are skipped and at most
+    /// `MAX_PAGE_SIZE` buckets are scanned from `from`.
+    pub fn get_candles(
+        env: Env,
+        token_id: u64,
+        interval: CandleInterval,
+        from: u64,
+        to: u64,
+    ) -> Vec<Candle> {
+        let mut candles = Vec::new(&env);
+        let mut open_time = interval.bucket(from);
+        let mut scanned = 0;
+        
+        while open_time <= to && scanned < MAX_PAGE_SIZE {
+            if let Some(candle) = get_candle(&env, token_id, interval, open_time) {
+                candles.push_back(candle);
+            }
+            open_time = match open_time.checked_add(interval.seconds()) {
+                Some(next) => next,
+                None => break,
+            };
+            scanned += 1;
+        }
+        
+        candles
+    }
+    
+    /// Get a trader's position in a token
+    pub fn get_position(env: Env, token_id: u64, trader: Address) -> Position {
+        get_position(&env, token_id, &trader).unwrap_or(Position::empty())
+    }
+    
+    /// Get number of traders currently holding a token
+    pub fn get_holder_count(env: Env, token_id: u64) -> u32 {
+        get_holder_count(&env, token_id)
+    }
+    
+    /// Get the largest holders of a token, biggest first
+    pub fn get_top_holders(env: Env, token_id: u64) -> Vec<Holder> {
+        get_top_holders(&env, token_id)
+    }
+    
+    /// Get the launches closest to graduation, hottest first
+    pub fn get_trending(env: Env, limit: u32) -> Vec<TrendingEntry> {
+        let trending = ranking::ranked(&env);
+        
+        if limit < trending.len() {
+            trending.slice(0..limit)
+        } else {
+            trending
+        }
+    }
+    
+    /// Get the most recently created launches, newest first
+    pub fn get_newest(env: Env, limit: u32) -> Vec<LaunchInfo> {
+        let count = get_token_counter(&env);
+        let end = Self::page_end(0, limit, count);
+        
+        let mut tokens = Vec::new(&env);
+        for offset in 0..end {
+            if let Some(token) = get_token(&env, count - offset) {
+                tokens.push_back(token);
+            }
+        }
+        
+        tokens
+    }
+    
+    /// Get number of trades recorded for a token
+    pub fn get_trade_count(env: Env, token_id: u64) -> u64 {
+        get_trade_counter(&env, token_id)
+    }
+    
+    /// Get up to `limit` trades for a token, oldest first, starting at trade `start`
+    pub fn get_trades(env: Env, token_id: u64, start: u64, limit: u32) -> Vec<TradeHistory> {
+        let end = Self::page_end(start, limit, get_trade_counter(&env, token_id));
+        let mut trades = Vec::new(&env);
+        
+        for trade_id in start..end {
+            if let Some(trade) = get_trade(&env, token_id, trade_id) {
+                trades.push_back(trade);
+            }
+        }
+        
+        trades
+    }
+    
+    /// Get up to `limit` trades made by `trader`, oldest first, starting at
+    /// the trader's `start`-th trade
+    pub fn get_trades_by_trader(
+        env: Env,
+        trader: Address,
+        start: u64,
+        limit: u32,
+    ) -> Vec<TradeHistory> {
+        let end = Self::page_end(start, limit, get_trader_trade_counter(&env, &trader));
+        let mut trades = Vec::new(&env);
+        
+        for index in start..end {
+            if let Some(trade_ref) = get_trader_trade(&env, &trader, index) {
+                if let Some(trade) = get_trade(&env, trade_ref.token_id, trade_ref.trade_id) {
+                    trades.push_back(trade);
+                }
+            }
+        }
+        
+        trades
+    }
+    
+    /// Internal: Exclusive end index of a page, bounded by `count`
+    fn page_end(start: u64, limit: u32, count: u64) -> u64 {
+        let limit = if limit > MAX_PAGE_SIZE { MAX_PAGE_SIZE } else { limit };
+        let end = start.saturating_add(limit as u64);
+        
+        if end < count {
+            end
+        } else {
+            count
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Events, Ledger},
+        token::StellarAssetClient,
+        Env, TryFromVal,
+    };
+
+    fn setup(e: &Env) -> (BondingCurveClient<'_>, Address, Address) {
+        e.mock_all_auths();
+
+        let contract_id = e.register_contract(None, BondingCurve);
+        let client = BondingCurveClient::new(e, &contract_id);
+
+        let xlm = e
+            .register_stellar_asset_contract_v2(Address::generate(e))
+            .address();
+
+        let amm_id = e.register_contract(None, ::amm::AMM);
+        ::amm::AMMClient::new(e, &amm_id).initialize(&xlm);
+
+        client.initialize(&Address::generate(e), &xlm, &amm_id);
+        let meme = new_meme(e, &client);
+        (client, xlm, meme)
+    }
+
+    /// Fresh token that has handed its mint authority to the curve
+    fn new_meme(e: &Env, client: &BondingCurveClient) -> Address {
+        e.register_stellar_asset_contract_v2(client.address.clone()).address()
+    }
+
+    fn launch(e: &Env, client: &BondingCurveClient, meme: &Address, supply: i128) -> u64 {
+        let curve = CurveKind::ConstantProduct(ConstantProductCurve {
+            virtual_xlm: 10_000_000_000,
+            virtual_tokens: supply / 10,
+        });
+        launch_with_curve(e, client, meme, supply, curve)
+    }
+
+    fn launch_with_curve(
+        e: &Env,
+        client: &BondingCurveClient,
+        meme: &Address,
+        supply: i128,
+        curve: CurveKind,
+    ) -> u64 {
+        let creator = Address::generate(e);
+        client.create_token(
+            &creator,
+            meme,
+            &String::from_str(e, "Doge"),
+            &String::from_str(e, "DOGE"),
+            &supply,
+            &curve,
+            &None,
+            &None,
+        )
+    }
+
+    #[test]
+    fn test_buy_and_sell_move_balances() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &1_000_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+        assert_eq!(meme_client.balance(&client.address), 1_000_000_000);
+
+        let tokens_out = client.buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None);
+        assert_eq!(meme_client.balance(&buyer), tokens_out);
+        assert_eq!(xlm_client.balance(&client.address), 100_000_000);
+        assert_eq!(client.get_token_info(&token_id).xlm_reserve, 99_000_000);
+
+        let xlm_out = client.sell(&buyer, &token_id, &tokens_out, &0, &u64::MAX, &None);
+        assert_eq!(meme_client.balance(&buyer), 0);
+        assert_eq!(xlm_client.balance(&buyer), 900_000_000 + xlm_out);
+        assert_eq!(
+            xlm_client.balance(&client.address),
+            client.get_token_info(&token_id).xlm_reserve
+                + client.get_accrued_fees(&client.get_admin())
+        );
+
+        assert_eq!(client.get_trade_count(&token_id), 2);
+        let trades = client.get_trades(&token_id, &1, &10);
+        assert_eq!(trades.len(), 1);
+        assert!(!trades.get(0).unwrap().is_buy);
+    }
+
+    #[test]
+    fn test_candles_aggregate_trades() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &300_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+
+        e.ledger().set_timestamp(1_200);
+        let first = client.buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None);
+        e.ledger().set_timestamp(1_230);
+        let second = client.buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None);
+        e.ledger().set_timestamp(1_300);
+        client.buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None);
+
+        let minutes = client.get_candles(&token_id, &CandleInterval::OneMinute, &0, &2_000);
+        assert_eq!(minutes.len(), 2);
+
+        let candle = minutes.get(0).unwrap();
+        assert_eq!(candle.open_time, 1_200);
+        assert_eq!(candle.trades, 2);
+        assert_eq!(candle.xlm_volume, 200_000_000);
+        assert_eq!(candle.token_volume, first + second);
+        assert!(candle.close > candle.open);
+        assert_eq!(candle.high, candle.close);
+
+        let days = client.get_candles(&token_id, &CandleInterval::OneDay, &0, &2_000);
+        assert_eq!(days.len(), 1);
+        assert_eq!(days.get(0).unwrap().trades, 3);
+
+        // Ranges at the end of time stop instead of overflowing
+        let end = u64::MAX;
+        let late = client.get_candles(&token_id, &CandleInterval::OneDay, &(end - 10), &end);
+        assert!(late.is_empty());
+    }
+
+    #[test]
+    fn test_trades_by_trader_span_tokens() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let buyer = Address::generate(&e);
+        let other = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &300_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&other, &100_000_000);
+
+        let first = launch(&e, &client, &meme, 1_000_000_000);
+        let second = launch(&e, &client, &new_meme(&e, &client), 1_000_000_000);
+
+        client.buy(&buyer, &first, &100_000_000, &0, &u64::MAX, &None);
+        client.buy(&other, &first, &100_000_000, &0, &u64::MAX, &None);
+        client.buy(&buyer, &second, &100_000_000, &0, &u64::MAX, &None);
+        client.buy(&buyer, &first, &100_000_000, &0, &u64::MAX, &None);
+
+        let trades = client.get_trades_by_trader(&buyer, &0, &2);
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades.get(0).unwrap().token_id, first);
+        assert_eq!(trades.get(1).unwrap().token_id, second);
+
+        let trades = client.get_trades_by_trader(&buyer, &2, &2);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades.get(0).unwrap().trade_id, 2);
+    }
+
+    #[test]
+    fn test_fees_split_and_claim() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+
+        let treasury = Address::generate(&e);
+        client.set_fee_config(&FeeConfig {
+            buy_fee_bps: 200,
+            sell_fee_bps: 200,
+            creator_share_bps: 5_000,
+            referrer_share_bps: 2_000,
+            treasury: treasury.clone(),
+        });
+
+        let buyer = Address::generate(&e);
+        let referrer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &100_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+        let creator = client.get_token_info(&token_id).creator;
+        client.buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &Some(referrer.clone()));
+
+        assert_eq!(client.get_token_info(&token_id).xlm_reserve, 98_000_000);
+        assert_eq!(client.get_accrued_fees(&creator), 1_000_000);
+        assert_eq!(client.get_accrued_fees(&referrer), 400_000);
+        assert_eq!(client.get_accrued_fees(&treasury), 600_000);
+
+        assert_eq!(client.claim_fees(&creator), 1_000_000);
+        assert_eq!(xlm_client.balance(&creator), 1_000_000);
+        assert_eq!(client.get_accrued_fees(&creator), 0);
+
+        // Shares that wrap around u32 are still rejected
+        let result = client.try_set_fee_config(&FeeConfig {
+            buy_fee_bps: 200,
+            sell_fee_bps: 200,
+            creator_share_bps: u32::MAX,
+            referrer_share_bps: 2,
+            treasury,
+        });
+        assert_eq!(result, Err(Ok(Error::InvalidFeeConfig)));
+    }
+
+    #[test]
+    fn test_curve_shapes_price_up_and_round_trip() {
+        let e = Env::default();
+        let (client, xlm, _) = setup(&e);
+
+        let curves = [
+            CurveKind::Linear(LinearCurve {
+                base_price: 1_000,
+                slope: 10,
+            }),
+            CurveKind::Exponential(ExponentialCurve {
+                base_price: 1_000,
+                growth_bps: 100,
+                step: 10_000_000,
+            }),
+            CurveKind::Stepped(soroban_sdk::vec![
+                &e,
+                CurveStep {
+                    supply: 50_000_000,
+                    price: 1_000,
+                },
+                CurveStep {
+                    supply: 1_000_000_000,
+                    price: 2_000,
+                },
+            ]),
+        ];
+
+        for curve in curves {
+            let buyer = Address::generate(&e);
+            StellarAssetClient::new(&e, &xlm).mint(&buyer, &100_000);
+
+            let meme = new_meme(&e, &client);
+            let token_id = launch_with_curve(&e, &client, &meme, 1_000_000_000, curve);
+            let start_price = client.get_price(&token_id);
+
+            let tokens_out = client.buy(&buyer, &token_id, &100_000, &0, &u64::MAX, &None);
+            assert!(tokens_out > 0);
+            assert!(client.get_price(&token_id) > start_price);
+
+            let xlm_out = client.sell(&buyer, &token_id, &tokens_out, &0, &u64::MAX, &None);
+            assert!(xlm_out <= 100_000);
+            assert_eq!(client.get_price(&token_id), start_price);
+        }
+    }
+
+    #[test]
+    fn test_large_supply_does_not_overflow() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let buyer = Address::generate(&e);
+        let xlm_amount = 10_000_000_000_000_000;
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &xlm_amount);
+
+        // Products of these reserves exceed i128 without 256-bit intermediates
+        let supply = 10_000_000_000_000_000_000_000_000;
+        let curve = CurveKind::ConstantProduct(ConstantProductCurve {
+            virtual_xlm: 1_000_000_000_000_000_000_000_000,
+            virtual_tokens: supply,
+        });
+        let token_id = launch_with_curve(&e, &client, &meme, supply, curve);
+
+        let quote = client.calculate_buy(&token_id, &xlm_amount);
+        let tokens_out = client.buy(&buyer, &token_id, &xlm_amount, &0, &u64::MAX, &None);
+        assert_eq!(tokens_out, quote.amount_out);
+        assert!(client.get_market_cap(&token_id) > 0);
+
+        let xlm_out = client.sell(&buyer, &token_id, &tokens_out, &0, &u64::MAX, &None);
+        assert!(xlm_out < xlm_amount);
+    }
+
+    #[test]
+    fn test_launch_rules_throttle_early_buys() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        e.ledger().set_sequence_number(100);
+
+        let creator = Address::generate(&e);
+        let supply = 1_000_000_000_000;
+        let token_id = client.create_token(
+            &creator,
+            &meme,
+            &String::from_str(&e, "Doge"),
+            &String::from_str(&e, "DOGE"),
+            &supply,
+            &CurveKind::ConstantProduct(ConstantProductCurve {
+                virtual_xlm: 10_000_000_000,
+                virtual_tokens: supply / 10,
+            }),
+            &Some(LaunchRules {
+                max_buy_per_wallet: 500_000_000,
+                max_buy_ledgers: 10,
+                buy_cooldown_ledgers: 5,
+                allowlist_ledgers: 3,
+            }),
+            &None,
+        );
+
+        let early = Address::generate(&e);
+        let sniper = Address::generate(&e);
+        for wallet in [&early, &sniper] {
+            StellarAssetClient::new(&e, &xlm).mint(wallet, &1_000_000_000);
+        }
+
+        // Allowlist phase
+        assert!(client.try_buy(&sniper, &token_id, &10_000_000, &0, &u64::MAX, &None).is_err());
+        client.set_allowlist(&token_id, &soroban_sdk::vec![&e, early.clone()], &true);
+        assert!(client.is_allowlisted(&token_id, &early));
+        client.buy(&early, &token_id, &10_000_000, &0, &u64::MAX, &None);
+
+        // Cooldown between buys from the same wallet
+        assert!(client.try_buy(&early, &token_id, &10_000_000, &0, &u64::MAX, &None).is_err());
+
+        // Per-wallet cap while the launch is young
+        e.ledger().set_sequence_number(105);
+        assert!(client.try_buy(&sniper, &token_id, &100_000_000, &0, &u64::MAX, &None).is_err());
+        client.buy(&sniper, &token_id, &10_000_000, &0, &u64::MAX, &None);
+
+        e.ledger().set_sequence_number(110);
+        assert!(client.buy(&sniper, &token_id, &100_000_000, &0, &u64::MAX, &None) > 500_000_000);
+    }
+
+    #[test]
+    fn test_dev_buy_vests_linearly() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let meme_client = token::Client::new(&e, &meme);
+        e.ledger().set_timestamp(1_000);
+
+        let creator = Address::generate(&e);
+        let supply = 1_000_000_000_000;
+        StellarAssetClient::new(&e, &xlm).mint(&creator, &100_000_000);
+        let token_id = client.create_token(
+            &creator,
+            &meme,
+            &String::from_str(&e, "Doge"),
+            &String::from_str(&e, "DOGE"),
+            &supply,
+            &CurveKind::ConstantProduct(ConstantProductCurve {
+                virtual_xlm: 10_000_000_000,
+                virtual_tokens: supply / 10,
+            }),
+            &None,
+            &Some(DevBuy {
+                xlm_amount: 100_000_000,
+                cliff_seconds: 100,
+                duration_seconds: 1_000,
+            }),
+        );
+
+        // The allocation is bought but stays locked in the curve
+        let info = client.get_token_info(&token_id);
+        assert!(info.vesting.total > 0);
+        assert_eq!(info.vesting.total, info.current_supply);
+        assert_eq!(info.vesting.cliff, 1_100);
+        assert_eq!(meme_client.balance(&creator), 0);
+        assert!(client.try_claim_vested(&token_id).is_err());
+
+        e.ledger().set_timestamp(1_500);
+        let half = client.claim_vested(&token_id);
+        assert_eq!(half, info.vesting.total / 2);
+
+        e.ledger().set_timestamp(3_000);
+        client.claim_vested(&token_id);
+        assert_eq!(meme_client.balance(&creator), info.vesting.total);
+        assert_eq!(client.get_token_info(&token_id).vesting.claimed, info.vesting.total);
+    }
+
+    #[test]
+    fn test_locked_dev_buy_cannot_be_sold() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let meme_client = token::Client::new(&e, &meme);
+        e.ledger().set_timestamp(1_000);
+
+        let creator = Address::generate(&e);
+        let friend = Address::generate(&e);
+        let supply = 1_000_000_000_000;
+        StellarAssetClient::new(&e, &xlm).mint(&creator, &100_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&friend, &100_000_000);
+        let token_id = client.create_token(
+            &creator,
+            &meme,
+            &String::from_str(&e, "Doge"),
+            &String::from_str(&e, "DOGE"),
+            &supply,
+            &CurveKind::ConstantProduct(ConstantProductCurve {
+                virtual_xlm: 10_000_000_000,
+                virtual_tokens: supply / 10,
+            }),
+            &None,
+            &Some(DevBuy {
+                xlm_amount: 100_000_000,
+                cliff_seconds: 0,
+                duration_seconds: 1_000,
+            }),
+        );
+        assert_eq!(client.get_position(&token_id, &creator).balance, 0);
+
+        // Tokens bought from a second wallet do not unlock the allocation
+        let bought = client.buy(&friend, &token_id, &100_000_000, &0, &u64::MAX, &None);
+        meme_client.transfer(&friend, &creator, &bought);
+        assert!(client.try_sell(&creator, &token_id, &bought, &0, &u64::MAX, &None).is_err());
+
+        e.ledger().set_timestamp(1_500);
+        let claimed = client.claim_vested(&token_id);
+        let position = client.get_position(&token_id, &creator);
+        assert_eq!(position.balance, claimed);
+        assert_eq!(position.xlm_spent, 50_000_000);
+
+        let too_many = claimed + 1;
+        assert!(client.try_sell(&creator, &token_id, &too_many, &0, &u64::MAX, &None).is_err());
+        client.sell(&creator, &token_id, &claimed, &0, &u64::MAX, &None);
+    }
+
+    #[test]
+    fn test_rejects_dev_buy_schedules_that_overflow() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let creator = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&creator, &100_000_000);
+        let launch = |duration_seconds: u64| {
+            client.try_create_token(
+                &creator,
+                &meme,
+                &String::from_str(&e, "Doge"),
+                &String::from_str(&e, "DOGE"),
+                &1_000_000_000,
+                &CurveKind::ConstantProduct(ConstantProductCurve {
+                    virtual_xlm: 10_000_000_000,
+                    virtual_tokens: 100_000_000,
+                }),
+                &None,
+                &Some(DevBuy {
+                    xlm_amount: 100_000_000,
+                    cliff_seconds: 0,
+                    duration_seconds,
+                }),
+            )
+        };
+        assert_eq!(launch(u64::MAX), Err(Ok(Error::InvalidDevBuy)));
+
+        e.ledger().set_timestamp(u64::MAX - 10);
+        assert_eq!(launch(1_000), Err(Ok(Error::InvalidDevBuy)));
+    }
+
+    #[test]
+    fn test_positions_track_holders() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let token_id = launch(&e, &client, &meme, 1_000_000_000_000);
+
+        let whale = Address::generate(&e);
+        let minnow = Address::generate(&e);
+        for (trader, amount) in [(&whale, 500_000_000), (&minnow, 100_000_000)] {
+            StellarAssetClient::new(&e, &xlm).mint(trader, &amount);
+            client.buy(trader, &token_id, &amount, &0, &u64::MAX, &None);
+        }
+
+        let position = client.get_position(&token_id, &whale);
+        assert_eq!(position.xlm_spent, 500_000_000);
+        assert_eq!(position.balance, token::Client::new(&e, &meme).balance(&whale));
+        assert_eq!(client.get_holder_count(&token_id), 2);
+
+        let top = client.get_top_holders(&token_id);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top.get(0).unwrap().trader, whale);
+
+        // Tokens received outside the curve cannot be sold into it
+        let outsider = Address::generate(&e);
+        token::Client::new(&e, &meme).transfer(&minnow, &outsider, &1_000);
+        assert!(client.try_sell(&outsider, &token_id, &1_000, &0, &u64::MAX, &None).is_err());
+
+        client.sell(&whale, &token_id, &position.balance, &0, &u64::MAX, &None);
+        assert_eq!(client.get_position(&token_id, &whale).balance, 0);
+        assert!(client.get_position(&token_id, &whale).xlm_received > 0);
+        assert_eq!(client.get_holder_count(&token_id), 1);
+        assert_eq!(client.get_top_holders(&token_id).get(0).unwrap().trader, minnow);
+    }
+
+    #[test]
+    fn test_trending_and_newest_rankings() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        e.ledger().set_timestamp(10_000);
+
+        let first = launch(&e, &client, &meme, 1_000_000_000_000);
+        let second = launch(&e, &client, &new_meme(&e, &client), 1_000_000_000_000);
+        let third = launch(&e, &client, &new_meme(&e, &client), 1_000_000_000_000);
+
+        let newest = client.get_newest(&2);
+        assert_eq!(newest.len(), 2);
+        assert_eq!(newest.get(0).unwrap().token_id, third);
+        assert_eq!(newest.get(1).unwrap().token_id, second);
+
+        let trader = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&trader, &1_000_000_000);
+        client.buy(&trader, &first, &100_000_000, &0, &u64::MAX, &None);
+        client.buy(&trader, &second, &500_000_000, &0, &u64::MAX, &None);
+
+        let trending = client.get_trending(&10);
+        assert_eq!(trending.len(), 2);
+        assert_eq!(trending.get(0).unwrap().token_id, second);
+        assert_eq!(trending.get(0).unwrap().volume_24h, 500_000_000);
+        assert!(trending.get(0).unwrap().progress_bps > trending.get(1).unwrap().progress_bps);
+        assert_eq!(client.get_trending(&1).len(), 1);
+
+        // Volumes fade once trading stops, and quiet launches drop out
+        e.ledger().set_timestamp(10_000 + 12 * 60 * 60);
+        assert_eq!(client.get_trending(&10).get(0).unwrap().volume_24h, 250_000_000);
+
+        e.ledger().set_timestamp(10_000 + 24 * 60 * 60);
+        client.buy(&trader, &third, &1_000_000, &0, &u64::MAX, &None);
+        let trending = client.get_trending(&10);
+        assert_eq!(trending.len(), 1);
+        assert_eq!(trending.get(0).unwrap().token_id, third);
+    }
+
+    #[test]
+    fn test_pause_and_refund_mode() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let token_id = launch(&e, &client, &meme, 1_000_000_000_000);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &200_000_000);
+        let tokens_out = client.buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None);
+
+        client.set_paused(&true);
+        assert!(client.try_buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None).is_err());
+        client.set_paused(&false);
+
+        client.set_token_paused(&token_id, &true);
+        assert!(client.is_token_paused(&token_id));
+        assert!(client.try_sell(&buyer, &token_id, &tokens_out, &0, &u64::MAX, &None).is_err());
+
+        // Refunds pay out the recorded XLM backing even while paused
+        let reserve = client.get_token_info(&token_id).xlm_reserve;
+        let price = client.enable_refund_mode(&token_id);
+        assert_eq!(client.get_refund_price(&token_id), Some(price));
+
+        let xlm_out = client.refund(&buyer, &token_id, &tokens_out);
+        assert!(xlm_out <= reserve);
+        assert!(reserve - xlm_out < 10);
+        assert_eq!(client.get_position(&token_id, &buyer).balance, 0);
+
+        client.set_token_paused(&token_id, &false);
+        assert!(client.try_buy(&buyer, &token_id, &100_000_000, &0, &u64::MAX, &None).is_err());
+    }
+
+    #[test]
+    fn test_admin_handover_and_events() {
+        let e = Env::default();
+        let (client, _, _) = setup(&e);
+        let admin = client.get_admin();
+        let new_admin = Address::generate(&e);
+
+        let fresh = BondingCurveClient::new(&e, &e.register_contract(None, BondingCurve));
+        fresh.initialize(&admin, &client.get_xlm_token(), &client.get_amm());
+        let event = e.events().all().last().unwrap();
+        assert_eq!(event.0, fresh.address);
+        assert_eq!(event.1, (events::INIT, admin.clone()).into_val(&e));
+
+        client.set_admin(&new_admin);
+        let auths = e.auths();
+        assert_eq!(auths.len(), 2);
+        assert_eq!(auths[0].0, admin);
+        assert_eq!(auths[1].0, new_admin);
+        assert_eq!(client.get_admin(), new_admin);
+
+        let event = e.events().all().last().unwrap();
+        assert_eq!(event.0, client.address);
+        assert_eq!(event.1, (events::ADMIN, admin).into_val(&e));
+        assert_eq!(Address::try_from_val(&e, &event.2), Ok(new_admin));
+    }
+
+    #[test]
+    fn test_rejects_invalid_curve() {
+        let e = Env::default();
+        let (client, _, meme) = setup(&e);
+        let creator = Address::generate(&e);
+
+        let curve = CurveKind::Stepped(soroban_sdk::vec![
+            &e,
+            CurveStep {
+                supply: 100,
+                price: 1_000,
+            },
+            CurveStep {
+                supply: 50,
+                price: 2_000,
+            },
+        ]);
+        let result = client.try_create_token(
+            &creator,
+            &meme,
+            &String::from_str(&e, "Doge"),
+            &String::from_str(&e, "DOGE"),
+            &1_000,
+            &curve,
+            &None,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidCurve)));
+
+        // Valid parameters, but the reserve overflows long before sell-out
+        let curve = CurveKind::Exponential(ExponentialCurve {
+            base_price: 1_000,
+            growth_bps: 100,
+            step: 10_000_000,
+        });
+        let result = client.try_create_token(
+            &creator,
+            &meme,
+            &String::from_str(&e, "Doge"),
+            &String::from_str(&e, "DOGE"),
+            &1_000_000_000_000_000,
+            &curve,
+            &None,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidCurve)));
+    }
+
+    #[test]
+    fn test_rejects_tokens_the_curve_does_not_control() {
+        let e = Env::default();
+        let (client, _, _) = setup(&e);
+        let creator = Address::generate(&e);
+
+        let curve = CurveKind::ConstantProduct(ConstantProductCurve {
+            virtual_xlm: 10_000_000_000,
+            virtual_tokens: 100_000_000,
+        });
+        let launch = |meme: &Address| {
+            client.try_create_token(
+                &creator,
+                meme,
+                &String::from_str(&e, "Doge"),
+                &String::from_str(&e, "DOGE"),
+                &1_000_000_000,
+                &curve,
+                &None,
+                &None,
+            )
+        };
+
+        // The creator still holds mint authority
+        let meme = e.register_stellar_asset_contract_v2(creator.clone()).address();
+        assert_eq!(launch(&meme), Err(Ok(Error::TokenAdminNotCurve)));
+
+        // The creator pre-minted before handing it over
+        StellarAssetClient::new(&e, &meme).mint(&creator, &1_000_000_000);
+        StellarAssetClient::new(&e, &meme).set_admin(&client.address);
+        assert_eq!(launch(&meme), Err(Ok(Error::TokenAlreadyMinted)));
+
+        let meme = new_meme(&e, &client);
+        assert!(launch(&meme).is_ok());
+        assert_eq!(token::Client::new(&e, &meme).balance(&client.address), 1_000_000_000);
+        assert_eq!(launch(&meme), Err(Ok(Error::TokenAlreadyLaunched)));
+    }
+
+    #[test]
+    fn test_buy_below_min_out() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &100_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+        let quote = client.calculate_buy(&token_id, &100_000_000);
+        let result = client.try_buy(
+            &buyer,
+            &token_id,
+            &100_000_000,
+            &(quote.amount_out + 1),
+            &u64::MAX,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::SlippageTooHigh)));
+    }
+
+    #[test]
+    fn test_quotes_report_price_impact() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let token_id = launch(&e, &client, &meme, 1_000_000_000_000);
+
+        let small = client.calculate_buy(&token_id, &10_000_000);
+        let large = client.calculate_buy(&token_id, &1_000_000_000);
+        assert_eq!(small.fee, 100_000);
+        assert_eq!(small.price_before, client.get_price(&token_id));
+        assert!(large.price_impact_bps > small.price_impact_bps);
+        assert!(large.avg_price > small.avg_price);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &1_000_000_000);
+        client.buy(&buyer, &token_id, &1_000_000_000, &0, &u64::MAX, &None);
+        assert_eq!(client.get_price(&token_id), large.price_after);
+
+        let sell = client.calculate_sell(&token_id, &large.amount_out);
+        assert_eq!(sell.price_after, large.price_before);
+        assert!(sell.avg_price < large.avg_price);
+    }
+
+    #[test]
+    fn test_exact_output_trades_match_quotes() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &1_000_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+
+        let xlm_quote = client.calculate_buy_exact_tokens(&token_id, &5_000_000);
+        let xlm_in = client.buy_exact_tokens(&buyer, &token_id, &5_000_000, &xlm_quote, &u64::MAX, &None);
+        assert_eq!(xlm_in, xlm_quote);
+        assert_eq!(token::Client::new(&e, &meme).balance(&buyer), 5_000_000);
+        assert_eq!(xlm_client.balance(&buyer), 1_000_000_000 - xlm_in);
+
+        let token_quote = client.calculate_sell_for_exact_xlm(&token_id, &1_000_000);
+        let tokens_in =
+            client.sell_for_exact_xlm(&buyer, &token_id, &1_000_000, &token_quote, &u64::MAX, &None);
+        assert_eq!(tokens_in, token_quote);
+        assert_eq!(xlm_client.balance(&buyer), 1_000_000_000 - xlm_in + 1_000_000);
+        assert!(client.calculate_sell(&token_id, &tokens_in).amount_out <= 1_000_000);
+    }
+
+    #[test]
+    fn test_sell_after_deadline() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &100_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+        let tokens_out = client.buy(&buyer, &token_id, &100_000_000, &0, &100, &None);
+
+        e.ledger().set_timestamp(101);
+        let result = client.try_sell(&buyer, &token_id, &tokens_out, &0, &100, &None);
+        assert_eq!(result, Err(Ok(Error::DeadlinePassed)));
+    }
+
+    #[test]
+    fn test_graduation_seeds_amm_pool() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let amm_id = client.get_amm();
+
+        client.set_graduation_config(&GraduationConfig {
+            market_cap: 0,
+            supply_bps: 500,
+        });
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &100_000_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+        let tokens_out = client.buy(&buyer, &token_id, &100_000_000_000, &0, &u64::MAX, &None);
+
+        let info = client.get_token_info(&token_id);
+        let pool_id = info.pool_id.unwrap();
+        assert_eq!(info.xlm_reserve, 0);
+
+        // The pool opens at the final curve price, the other unsold tokens
+        // are burned
+        let pool = ::amm::AMMClient::new(&e, &amm_id).get_pool(&pool_id);
+        assert_eq!(pool.xlm_reserve, 99_000_000_000);
+        let curve_price = client.get_price(&token_id);
+        let pool_price = pool.xlm_reserve * PRICE_SCALE / pool.token_reserve;
+        assert!(pool_price >= curve_price && pool_price - curve_price <= curve_price / 10_000);
+        assert!(pool.token_reserve < 1_000_000_000 - tokens_out);
+        assert_eq!(info.total_supply, tokens_out + pool.token_reserve);
+        assert_eq!(token::Client::new(&e, &xlm).balance(&amm_id), 99_000_000_000);
+        assert_eq!(token::Client::new(&e, &meme).balance(&client.address), 0);
+        assert!(client.get_trending(&10).is_empty());
+
+        assert_eq!(
+            client.try_set_graduation_config(&GraduationConfig {
+                market_cap: 0,
+                supply_bps: 10_000,
+            }),
+            Err(Ok(Error::InvalidGraduationConfig))
+        );
+    }
+
+    #[test]
+    fn test_graduation_keeps_supply_for_the_pool() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        client.set_graduation_config(&GraduationConfig {
+            market_cap: 0,
+            supply_bps: 5_000,
+        });
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &1_000_000_000_000);
+
+        let curve = CurveKind::Linear(LinearCurve {
+            base_price: 1_000,
+            slope: 10,
+        });
+        let token_id = launch_with_curve(&e, &client, &meme, 1_000_000_000, curve);
+
+        let result = client.try_buy_exact_tokens(
+            &buyer,
+            &token_id,
+            &1_000_000_000,
+            &i128::MAX,
+            &u64::MAX,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::ExceedsAvailableSupply)));
+
+        client.buy_exact_tokens(&buyer, &token_id, &999_000_000, &i128::MAX, &u64::MAX, &None);
+        assert!(client.get_token_info(&token_id).pool_id.is_some());
+    }
+
+    #[test]
+    fn test_no_trading_after_graduation() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        client.set_graduation_config(&GraduationConfig {
+            market_cap: 1,
+            supply_bps: 0,
+        });
+
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &2_000_000_000);
+
+        let token_id = launch(&e, &client, &meme, 1_000_000_000);
+        client.buy(&buyer, &token_id, &1_000_000_000, &0, &u64::MAX, &None);
+
+        let result = client.try_buy(&buyer, &token_id, &1_000_000_000, &0, &u64::MAX, &None);
+        assert_eq!(result, Err(Ok(Error::TokenGraduated)));
     }
 }
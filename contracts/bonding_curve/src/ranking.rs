@@ -0,0 +1,111 @@
+use meme_common::math::{mul_div, Rounding};
+use soroban_sdk::{contracttype, Env, Vec};
+
+use crate::candles::CandleInterval;
+use crate::storage::{get_candle, get_trending, set_trending};
+
+/// Size of the trending leaderboard
+pub const MAX_TRENDING: u32 = 20;
+
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// A launch's standing on the trending leaderboard. `volume_24h` decays
+/// with the time since `last_trade`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TrendingEntry {
+    pub token_id: u64,
+    pub progress_bps: u32,
+    pub volume_24h: i128,
+    pub last_trade: u64,
+}
+
+impl TrendingEntry {
+    /// Ranks by graduation progress, then 24h volume, then latest trade
+    fn outranks(&self, other: &TrendingEntry) -> bool {
+        (self.progress_bps, self.volume_24h, self.last_trade)
+            > (other.progress_bps, other.volume_24h, other.last_trade)
+    }
+
+    /// The entry at `now`, or `None` once the launch has been quiet for a
+    /// day. Its 24h volume was measured at its last trade, so it decays
+    /// linearly from there.
+    fn at(mut self, e: &Env, now: u64) -> Option<TrendingEntry> {
+        let quiet = now.saturating_sub(self.last_trade);
+        if quiet >= DAY_SECONDS {
+            return None;
+        }
+
+        let (remaining, day) = ((DAY_SECONDS - quiet) as i128, DAY_SECONDS as i128);
+        let volume = mul_div(e, self.volume_24h, remaining, day, Rounding::Down);
+        self.volume_24h = volume.unwrap_or(0);
+        Some(self)
+    }
+}
+
+/// Rolling 24h XLM volume of a launch, summed from its hourly candles
+pub fn volume_24h(e: &Env, token_id: u64) -> i128 {
+    let interval = CandleInterval::OneHour;
+    let mut open_time = interval.bucket(e.ledger().timestamp());
+
+    let mut volume = 0;
+    for _ in 0..24 {
+        if let Some(candle) = get_candle(e, token_id, interval, open_time) {
+            volume += candle.xlm_volume;
+        }
+        if open_time < interval.seconds() {
+            break;
+        }
+        open_time -= interval.seconds();
+    }
+
+    volume
+}
+
+/// The leaderboard as of now, re-ranked with stale volumes decayed and
+/// launches that stopped trading dropped
+pub fn ranked(e: &Env) -> Vec<TrendingEntry> {
+    let now = e.ledger().timestamp();
+
+    let mut trending = Vec::new(e);
+    for entry in get_trending(e).iter() {
+        if let Some(entry) = entry.at(e, now) {
+            insert(&mut trending, entry);
+        }
+    }
+
+    trending
+}
+
+/// Refresh a launch's place on the leaderboard. `None` drops it, e.g. once
+/// it graduates.
+pub fn update(e: &Env, token_id: u64, entry: Option<TrendingEntry>) {
+    let mut trending = Vec::new(e);
+    for existing in ranked(e).iter() {
+        if existing.token_id != token_id {
+            trending.push_back(existing);
+        }
+    }
+
+    if let Some(entry) = entry {
+        insert(&mut trending, entry);
+    }
+
+    while trending.len() > MAX_TRENDING {
+        trending.pop_back();
+    }
+
+    set_trending(e, &trending);
+}
+
+/// Insert `entry` after every entry that ranks at least as high
+fn insert(trending: &mut Vec<TrendingEntry>, entry: TrendingEntry) {
+    let mut index = trending.len();
+    for (i, existing) in trending.iter().enumerate() {
+        if entry.outranks(&existing) {
+            index = i as u32;
+            break;
+        }
+    }
+    trending.insert(index, entry);
+}
@@ -0,0 +1,366 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::candles::{Candle, CandleInterval};
+use crate::errors::Error;
+use crate::fees::FeeConfig;
+use crate::holders::{Holder, Position};
+use crate::launch::{LaunchRules, LaunchWallet};
+use crate::ranking::TrendingEntry;
+use crate::types::{GraduationConfig, LaunchInfo, TradeHistory, TradeRef};
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Admin,
+    XlmToken,
+    Amm,
+    GraduationConfig,
+    FeeConfig,
+    Paused,
+    TokenCounter,
+    Token(u64),
+    Launched(Address),
+    TradeCounter(u64),
+    Trade(u64, u64),
+    TraderTradeCounter(Address),
+    TraderTrade(Address, u64),
+    Candle(u64, CandleInterval, u64),
+    AccruedFees(Address),
+    LaunchRules(u64),
+    Allowlist(u64, Address),
+    LaunchWallet(u64, Address),
+    Position(u64, Address),
+    HolderCount(u64),
+    TopHolders(u64),
+    Trending,
+    TokenPaused(u64),
+    RefundPrice(u64),
+}
+
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+fn extend_persistent(e: &Env, key: &DataKey) {
+    e.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+}
+
+pub fn has_admin(e: &Env) -> bool {
+    e.storage().instance().has(&DataKey::Admin)
+}
+
+pub fn get_admin(e: &Env) -> Result<Address, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_admin(e: &Env, admin: &Address) {
+    e.storage().instance().set(&DataKey::Admin, admin);
+}
+
+pub fn is_paused(e: &Env) -> bool {
+    e.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+}
+
+pub fn set_paused(e: &Env, paused: bool) {
+    e.storage().instance().set(&DataKey::Paused, &paused);
+}
+
+pub fn get_xlm_token(e: &Env) -> Result<Address, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::XlmToken)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_xlm_token(e: &Env, xlm_token: &Address) {
+    e.storage().instance().set(&DataKey::XlmToken, xlm_token);
+}
+
+pub fn get_amm(e: &Env) -> Result<Address, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::Amm)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_amm(e: &Env, amm: &Address) {
+    e.storage().instance().set(&DataKey::Amm, amm);
+}
+
+pub fn get_graduation_config(e: &Env) -> Result<GraduationConfig, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::GraduationConfig)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_graduation_config(e: &Env, config: &GraduationConfig) {
+    e.storage()
+        .instance()
+        .set(&DataKey::GraduationConfig, config);
+}
+
+pub fn get_fee_config(e: &Env) -> Result<FeeConfig, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::FeeConfig)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_fee_config(e: &Env, config: &FeeConfig) {
+    e.storage().instance().set(&DataKey::FeeConfig, config);
+}
+
+pub fn get_token_counter(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::TokenCounter)
+        .unwrap_or(0)
+}
+
+pub fn set_token_counter(e: &Env, counter: u64) {
+    e.storage().instance().set(&DataKey::TokenCounter, &counter);
+}
+
+pub fn get_token(e: &Env, token_id: u64) -> Option<LaunchInfo> {
+    let key = DataKey::Token(token_id);
+    let token = e.storage().persistent().get(&key);
+    if token.is_some() {
+        extend_persistent(e, &key);
+    }
+    token
+}
+
+pub fn set_token(e: &Env, token: &LaunchInfo) {
+    let key = DataKey::Token(token.token_id);
+    e.storage().persistent().set(&key, token);
+    extend_persistent(e, &key);
+}
+
+pub fn is_launched(e: &Env, token_address: &Address) -> bool {
+    e.storage()
+        .persistent()
+        .has(&DataKey::Launched(token_address.clone()))
+}
+
+pub fn set_launched(e: &Env, token_address: &Address, token_id: u64) {
+    let key = DataKey::Launched(token_address.clone());
+    e.storage().persistent().set(&key, &token_id);
+    extend_persistent(e, &key);
+}
+
+pub fn get_trade_counter(e: &Env, token_id: u64) -> u64 {
+    let key = DataKey::TradeCounter(token_id);
+    let counter = e.storage().persistent().get(&key);
+    if counter.is_some() {
+        extend_persistent(e, &key);
+    }
+    counter.unwrap_or(0)
+}
+
+pub fn set_trade_counter(e: &Env, token_id: u64, counter: u64) {
+    let key = DataKey::TradeCounter(token_id);
+    e.storage().persistent().set(&key, &counter);
+    extend_persistent(e, &key);
+}
+
+pub fn get_trade(e: &Env, token_id: u64, trade_id: u64) -> Option<TradeHistory> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::Trade(token_id, trade_id))
+}
+
+pub fn set_trade(e: &Env, token_id: u64, trade_id: u64, trade: &TradeHistory) {
+    let key = DataKey::Trade(token_id, trade_id);
+    e.storage().persistent().set(&key, trade);
+    extend_persistent(e, &key);
+}
+
+pub fn get_trader_trade_counter(e: &Env, trader: &Address) -> u64 {
+    let key = DataKey::TraderTradeCounter(trader.clone());
+    let counter = e.storage().persistent().get(&key);
+    if counter.is_some() {
+        extend_persistent(e, &key);
+    }
+    counter.unwrap_or(0)
+}
+
+pub fn add_trader_trade(e: &Env, trader: &Address, trade: &TradeRef) {
+    let counter = get_trader_trade_counter(e, trader);
+
+    let key = DataKey::TraderTrade(trader.clone(), counter);
+    e.storage().persistent().set(&key, trade);
+    extend_persistent(e, &key);
+
+    let key = DataKey::TraderTradeCounter(trader.clone());
+    e.storage().persistent().set(&key, &(counter + 1));
+    extend_persistent(e, &key);
+}
+
+pub fn get_trader_trade(e: &Env, trader: &Address, index: u64) -> Option<TradeRef> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::TraderTrade(trader.clone(), index))
+}
+
+pub fn get_candle(
+    e: &Env,
+    token_id: u64,
+    interval: CandleInterval,
+    open_time: u64,
+) -> Option<Candle> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::Candle(token_id, interval, open_time))
+}
+
+pub fn set_candle(e: &Env, token_id: u64, interval: CandleInterval, candle: &Candle) {
+    let key = DataKey::Candle(token_id, interval, candle.open_time);
+    e.storage().persistent().set(&key, candle);
+    extend_persistent(e, &key);
+}
+
+pub fn get_accrued_fees(e: &Env, recipient: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::AccruedFees(recipient.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_accrued_fees(e: &Env, recipient: &Address, amount: i128) {
+    let key = DataKey::AccruedFees(recipient.clone());
+    if amount == 0 {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, &amount);
+        extend_persistent(e, &key);
+    }
+}
+
+pub fn get_launch_rules(e: &Env, token_id: u64) -> Option<LaunchRules> {
+    e.storage().persistent().get(&DataKey::LaunchRules(token_id))
+}
+
+pub fn set_launch_rules(e: &Env, token_id: u64, rules: &LaunchRules) {
+    let key = DataKey::LaunchRules(token_id);
+    e.storage().persistent().set(&key, rules);
+    extend_persistent(e, &key);
+}
+
+pub fn is_allowlisted(e: &Env, token_id: u64, wallet: &Address) -> bool {
+    e.storage()
+        .persistent()
+        .has(&DataKey::Allowlist(token_id, wallet.clone()))
+}
+
+pub fn set_allowlisted(e: &Env, token_id: u64, wallet: &Address, allowed: bool) {
+    let key = DataKey::Allowlist(token_id, wallet.clone());
+    if allowed {
+        e.storage().persistent().set(&key, &true);
+        extend_persistent(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub fn get_launch_wallet(e: &Env, token_id: u64, wallet: &Address) -> Option<LaunchWallet> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::LaunchWallet(token_id, wallet.clone()))
+}
+
+pub fn set_launch_wallet(e: &Env, token_id: u64, wallet: &Address, state: &LaunchWallet) {
+    let key = DataKey::LaunchWallet(token_id, wallet.clone());
+    e.storage().persistent().set(&key, state);
+    extend_persistent(e, &key);
+}
+
+pub fn get_position(e: &Env, token_id: u64, trader: &Address) -> Option<Position> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::Position(token_id, trader.clone()))
+}
+
+pub fn set_position(e: &Env, token_id: u64, trader: &Address, position: &Position) {
+    let key = DataKey::Position(token_id, trader.clone());
+    e.storage().persistent().set(&key, position);
+    extend_persistent(e, &key);
+}
+
+pub fn get_holder_count(e: &Env, token_id: u64) -> u32 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::HolderCount(token_id))
+        .unwrap_or(0)
+}
+
+pub fn set_holder_count(e: &Env, token_id: u64, count: u32) {
+    let key = DataKey::HolderCount(token_id);
+    e.storage().persistent().set(&key, &count);
+    extend_persistent(e, &key);
+}
+
+pub fn get_top_holders(e: &Env, token_id: u64) -> Vec<Holder> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::TopHolders(token_id))
+        .unwrap_or(Vec::new(e))
+}
+
+pub fn set_top_holders(e: &Env, token_id: u64, holders: &Vec<Holder>) {
+    let key = DataKey::TopHolders(token_id);
+    e.storage().persistent().set(&key, holders);
+    extend_persistent(e, &key);
+}
+
+pub fn get_trending(e: &Env) -> Vec<TrendingEntry> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::Trending)
+        .unwrap_or(Vec::new(e))
+}
+
+pub fn set_trending(e: &Env, trending: &Vec<TrendingEntry>) {
+    let key = DataKey::Trending;
+    e.storage().persistent().set(&key, trending);
+    extend_persistent(e, &key);
+}
+
+pub fn is_token_paused(e: &Env, token_id: u64) -> bool {
+    e.storage().persistent().has(&DataKey::TokenPaused(token_id))
+}
+
+pub fn set_token_paused(e: &Env, token_id: u64, paused: bool) {
+    let key = DataKey::TokenPaused(token_id);
+    if paused {
+        e.storage().persistent().set(&key, &true);
+        extend_persistent(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub fn get_refund_price(e: &Env, token_id: u64) -> Option<i128> {
+    e.storage().persistent().get(&DataKey::RefundPrice(token_id))
+}
+
+pub fn set_refund_price(e: &Env, token_id: u64, price: i128) {
+    let key = DataKey::RefundPrice(token_id);
+    e.storage().persistent().set(&key, &price);
+    extend_persistent(e, &key);
+}
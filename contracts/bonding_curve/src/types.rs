@@ -0,0 +1,67 @@
+use soroban_sdk::{contracttype, Address, String};
+
+use crate::curve::CurveKind;
+use crate::vesting::Vesting;
+
+/// A token's launch on the curve, from creation until it graduates
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LaunchInfo {
+    pub token_id: u64,
+    pub name: String,
+    pub symbol: String,
+    pub token_address: Address,
+    pub total_supply: i128,
+    pub current_supply: i128,
+    pub xlm_reserve: i128,
+    pub curve: CurveKind,
+    pub creator: Address,
+    pub created_at: u64,
+    pub launch_ledger: u32,
+    pub vesting: Vesting,
+    pub pool_id: Option<u64>,
+}
+
+/// Thresholds at which a launch graduates to an AMM pool. A zero value
+/// disables that threshold.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct GraduationConfig {
+    pub market_cap: i128,
+    pub supply_bps: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TradeHistory {
+    pub token_id: u64,
+    pub trade_id: u64,
+    pub trader: Address,
+    pub is_buy: bool,
+    pub token_amount: i128,
+    pub xlm_amount: i128,
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Locates a trade from a trader's index
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TradeRef {
+    pub token_id: u64,
+    pub trade_id: u64,
+}
+
+/// Preview of a trade. Prices are in stroops per whole token, the fee is in
+/// XLM and the price impact is how far the trade moves the spot price.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Quote {
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub fee: i128,
+    pub avg_price: i128,
+    pub price_before: i128,
+    pub price_after: i128,
+    pub price_impact_bps: u32,
+}
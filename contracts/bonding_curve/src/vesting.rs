@@ -0,0 +1,95 @@
+use meme_common::math::{mul_div, Rounding};
+use soroban_sdk::{contracttype, Env};
+
+use crate::errors::Error;
+
+/// Longest vesting schedule a dev buy can ask for, ten years
+const MAX_DURATION_SECONDS: u64 = 10 * 365 * 24 * 60 * 60;
+
+/// Tokens the creator buys atomically at launch, locked under a vesting schedule
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DevBuy {
+    pub xlm_amount: i128,
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+}
+
+/// Linear vesting with a cliff. Nothing unlocks before `cliff`, everything
+/// is unlocked at `end`. `cost` is the XLM paid for `total`. A zero `total`
+/// means the launch has no dev buy.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Vesting {
+    pub total: i128,
+    pub cost: i128,
+    pub claimed: i128,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+impl DevBuy {
+    /// Fails if the dev buy is unusable
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.xlm_amount <= 0
+            || self.duration_seconds == 0
+            || self.duration_seconds > MAX_DURATION_SECONDS
+            || self.cliff_seconds > self.duration_seconds
+        {
+            return Err(Error::InvalidDevBuy);
+        }
+        Ok(())
+    }
+
+    /// Schedule locking `total` tokens from `start`
+    pub fn schedule(&self, total: i128, start: u64) -> Result<Vesting, Error> {
+        let cliff = start.checked_add(self.cliff_seconds);
+        let end = start.checked_add(self.duration_seconds);
+        match (cliff, end) {
+            (Some(cliff), Some(end)) => Ok(Vesting {
+                total,
+                cost: self.xlm_amount,
+                claimed: 0,
+                start,
+                cliff,
+                end,
+            }),
+            _ => Err(Error::InvalidDevBuy),
+        }
+    }
+}
+
+impl Vesting {
+    /// Schedule for a launch without a dev buy
+    pub fn none() -> Self {
+        Vesting {
+            total: 0,
+            cost: 0,
+            claimed: 0,
+            start: 0,
+            cliff: 0,
+            end: 0,
+        }
+    }
+
+    /// Tokens unlocked at `now`, rounded down
+    pub fn vested(&self, e: &Env, now: u64) -> Result<i128, Error> {
+        if now < self.cliff {
+            Ok(0)
+        } else if now >= self.end {
+            Ok(self.total)
+        } else {
+            let elapsed = (now - self.start) as i128;
+            let duration = (self.end - self.start) as i128;
+            Ok(mul_div(e, self.total, elapsed, duration, Rounding::Down)?)
+        }
+    }
+
+    /// XLM paid for the tokens claimed between `from` and `to`, rounded so
+    /// that claims add up to `cost`
+    pub fn cost_of(&self, e: &Env, from: i128, to: i128) -> Result<i128, Error> {
+        let paid_to = mul_div(e, self.cost, to, self.total, Rounding::Down)?;
+        Ok(paid_to - mul_div(e, self.cost, from, self.total, Rounding::Down)?)
+    }
+}
@@ -0,0 +1,38 @@
+use meme_common::MathError;
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    OrderNotFound = 3,
+    OrderNotActive = 4,
+    InsufficientOrderAmount = 5,
+    NotOrderOwner = 6,
+    InvalidAmount = 7,
+    InvalidPrice = 8,
+    WrongOrderSide = 9,
+    InvalidExpiry = 10,
+    OrderExpired = 11,
+    OrderNotFilled = 12,
+    InvalidFeeConfig = 13,
+    NoFeesToClaim = 14,
+    OrderTooSmall = 15,
+    PriceLevelFull = 16,
+    OrderBookFull = 17,
+    Overflow = 100,
+    DivisionByZero = 101,
+    NegativeValue = 102,
+}
+
+impl From<MathError> for Error {
+    fn from(err: MathError) -> Self {
+        match err {
+            MathError::Overflow => Error::Overflow,
+            MathError::DivisionByZero => Error::DivisionByZero,
+            MathError::NegativeValue => Error::NegativeValue,
+        }
+    }
+}
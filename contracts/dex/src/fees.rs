@@ -0,0 +1,73 @@
+use meme_common::math::{mul_div, Rounding};
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::errors::Error;
+
+pub const BPS: i128 = 10_000;
+
+/// Fees for traders who have traded at least `min_volume` XLM
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeTier {
+    pub min_volume: i128,
+    pub maker_fee_bps: u32,
+    pub taker_fee_bps: u32,
+}
+
+/// Maker and taker fees and the treasury that claims them. Traders move up
+/// `tiers`, ordered by volume, as their XLM volume on the DEX grows.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeConfig {
+    pub maker_fee_bps: u32,
+    pub taker_fee_bps: u32,
+    pub treasury: Address,
+    pub tiers: Vec<FeeTier>,
+}
+
+impl FeeConfig {
+    /// Schedule charging nothing, claimable by `treasury`
+    pub fn none(e: &Env, treasury: &Address) -> Self {
+        FeeConfig {
+            maker_fee_bps: 0,
+            taker_fee_bps: 0,
+            treasury: treasury.clone(),
+            tiers: Vec::new(e),
+        }
+    }
+
+    /// Fails if the fee schedule is unusable
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut valid = (self.maker_fee_bps as i128) < BPS && (self.taker_fee_bps as i128) < BPS;
+        let mut prev = 0;
+        for tier in self.tiers.iter() {
+            valid = valid
+                && tier.min_volume > prev
+                && (tier.maker_fee_bps as i128) < BPS
+                && (tier.taker_fee_bps as i128) < BPS;
+            prev = tier.min_volume;
+        }
+
+        if !valid {
+            return Err(Error::InvalidFeeConfig);
+        }
+        Ok(())
+    }
+
+    /// Maker and taker fees for a trader with `volume` XLM traded
+    pub fn rates(&self, volume: i128) -> (u32, u32) {
+        let mut rates = (self.maker_fee_bps, self.taker_fee_bps);
+        for tier in self.tiers.iter() {
+            if volume < tier.min_volume {
+                break;
+            }
+            rates = (tier.maker_fee_bps, tier.taker_fee_bps);
+        }
+        rates
+    }
+}
+
+/// Fee of `fee_bps` charged on `amount`, rounded up
+pub fn fee_on(e: &Env, amount: i128, fee_bps: u32) -> Result<i128, Error> {
+    Ok(mul_div(e, amount, fee_bps as i128, BPS, Rounding::Up)?)
+}
@@ -1,181 +1,1566 @@
 #![no_std]
 
-use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, Map, Symbol, Vec,
+mod errors;
+mod fees;
+mod storage;
+mod types;
+
+use meme_common::math::{mul_div, Rounding, PRICE_SCALE};
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, Vec};
+
+pub use errors::Error;
+pub use fees::{FeeConfig, FeeTier};
+pub use types::{
+    BestBidAsk, Depth, FillSummary, Order, OrderBook, OrderStatus, PriceLevel, Side, TimeInForce,
+    Trade,
 };
+use storage::*;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[contracttype]
-pub struct SellOrder {
-    pub order_id: u64,
-    pub seller: Address,
-    pub token_contract: Address,
-    pub amount: i128,
-    pub price_per_token: i128,
-    pub created_at: u64,
-    pub is_active: bool,
-}
+/// Upper bound on the number of orders one `purge_expired` call looks at
+const MAX_PURGE: u32 = 50;
+
+/// Upper bound on the number of entries a paginated query returns
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Upper bounds on the resting orders at one price and the prices on one
+/// side of a book, and on the orders one taker order fills against. They
+/// keep matching within the compute budget.
+const MAX_LEVEL_ORDERS: u32 = 32;
+const MAX_PRICE_LEVELS: u32 = 64;
+const MAX_FILLS: u32 = 32;
+
+/// XLM a resting order must be worth at first, 1 XLM
+const DEFAULT_MIN_ORDER_VALUE: i128 = 10_000_000;
+
+/// Tokens a resting order must be for at first, one whole token
+const DEFAULT_MIN_ORDER_AMOUNT: i128 = PRICE_SCALE;
 
 #[contract]
 pub struct DEX;
 
 #[contractimpl]
 impl DEX {
-    /// Create a sell order
+    /// Initialize the DEX with an admin and the native XLM Stellar Asset
+    /// Contract that fills are paid in
+    pub fn initialize(env: Env, admin: Address, xlm_token: Address) -> Result<(), Error> {
+        if has_admin(&env) {
+            return Err(Error::AlreadyInitialized);
+        }
+        admin.require_auth();
+        
+        set_admin(&env, &admin);
+        set_xlm_token(&env, &xlm_token);
+        set_min_order_value(&env, DEFAULT_MIN_ORDER_VALUE);
+        set_min_order_amount(&env, DEFAULT_MIN_ORDER_AMOUNT);
+        set_fee_config(&env, &FeeConfig::none(&env, &admin));
+        
+        Ok(())
+    }
+    
+    /// Set the XLM value, in stroops, an order must be worth to rest on the
+    /// book (admin only)
+    pub fn set_min_order_value(env: Env, value: i128) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        
+        if value < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        extend_instance(&env);
+        set_min_order_value(&env, value);
+        
+        Ok(())
+    }
+    
+    /// Get the XLM value, in stroops, an order must be worth to rest on the
+    /// book
+    pub fn get_min_order_value(env: Env) -> i128 {
+        get_min_order_value(&env)
+    }
+    
+    /// Set the number of tokens an order must be for to rest on the book
+    /// (admin only)
+    pub fn set_min_order_amount(env: Env, amount: i128) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        
+        if amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        extend_instance(&env);
+        set_min_order_amount(&env, amount);
+        
+        Ok(())
+    }
+    
+    /// Get the number of tokens an order must be for to rest on the book
+    pub fn get_min_order_amount(env: Env) -> i128 {
+        get_min_order_amount(&env)
+    }
+    
+    /// Get admin address
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        get_admin(&env)
+    }
+    
+    /// Get the native XLM token address used for settlement
+    pub fn get_xlm_token(env: Env) -> Result<Address, Error> {
+        get_xlm_token(&env)
+    }
+    
+    /// Set maker and taker fees, their volume tiers and the treasury
+    /// (admin only)
+    pub fn set_fee_config(env: Env, config: FeeConfig) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        
+        config.validate()?;
+        
+        extend_instance(&env);
+        set_fee_config(&env, &config);
+        
+        env.events().publish((symbol_short!("FEECFG"),), config);
+        
+        Ok(())
+    }
+    
+    /// Get maker and taker fees, their volume tiers and the treasury
+    pub fn get_fee_config(env: Env) -> Result<FeeConfig, Error> {
+        get_fee_config(&env)
+    }
+    
+    /// Get the maker and taker fees `trader` currently pays, in basis points
+    pub fn get_fee_rates(env: Env, trader: Address) -> Result<(u32, u32), Error> {
+        Ok(get_fee_config(&env)?.rates(get_trader_volume(&env, &trader)))
+    }
+    
+    /// Get the XLM volume `trader` has traded on the DEX as maker or taker
+    pub fn get_trader_volume(env: Env, trader: Address) -> i128 {
+        get_trader_volume(&env, &trader)
+    }
+    
+    /// Get fees accrued in `asset`, XLM or a traded token, and not yet claimed
+    pub fn get_accrued_fees(env: Env, asset: Address) -> i128 {
+        get_accrued_fees(&env, &asset)
+    }
+    
+    /// Send all fees accrued in `asset` to the treasury, returns the amount
+    /// claimed (treasury only)
+    pub fn claim_fees(env: Env, asset: Address) -> Result<i128, Error> {
+        let treasury = get_fee_config(&env)?.treasury;
+        treasury.require_auth();
+        
+        extend_instance(&env);
+        let amount = get_accrued_fees(&env, &asset);
+        if amount <= 0 {
+            return Err(Error::NoFeesToClaim);
+        }
+        
+        set_accrued_fees(&env, &asset, 0);
+        
+        token::Client::new(&env, &asset).transfer(
+            &env.current_contract_address(),
+            &treasury,
+            &amount,
+        );
+        
+        env.events().publish((symbol_short!("CLAIM"), asset), (treasury, amount));
+        
+        Ok(amount)
+    }
+    
+    /// Place a limit order selling `amount` tokens at `price` stroops per
+    /// whole token or better
+    ///
+    /// The order first fills against resting bids at `price` or higher, best
+    /// price first. Depending on `time_in_force` any remainder rests on the
+    /// book, until `expires_at` for good-til-time orders, with its tokens
+    /// held in escrow by the DEX until it is filled, cancelled or purged.
+    /// An order that fills `MAX_FILLS` bids while more still cross its price
+    /// drops its remainder like an immediate-or-cancel order.
     pub fn create_sell_order(
         env: Env,
         seller: Address,
-        token_contract: Address,
+        token_address: Address,
         amount: i128,
-        price_per_token: i128,
-    ) -> u64 {
+        price: i128,
+        time_in_force: TimeInForce,
+        expires_at: u64,
+    ) -> Result<FillSummary, Error> {
         seller.require_auth();
+        Self::check_order(amount, price)?;
+        let expires_at = Self::check_expiry(&env, time_in_force, expires_at)?;
+        
+        extend_instance(&env);
+        let mut summary =
+            Self::match_orders(&env, &seller, &token_address, Side::Buy, amount, price)?;
+        
+        let remainder = amount - summary.filled;
+        if Self::rests(time_in_force, remainder)?
+            && !Self::cut_short(&env, &summary, &token_address, Side::Buy, price)
+        {
+            let order_id = Self::open_order(
+                &env,
+                &seller,
+                &token_address,
+                Side::Sell,
+                remainder,
+                price,
+                expires_at,
+            )?;
+            summary.order_id = Some(order_id);
+            
+            // Escrow the tokens left for sale
+            token::Client::new(&env, &token_address).transfer(
+                &seller,
+                &env.current_contract_address(),
+                &remainder,
+            );
+        }
         
-        let orders_key = symbol_short!("ORDERS");
-        let count_key = symbol_short!("ORD_CNT");
+        Ok(summary)
+    }
+    
+    /// Place a limit order buying `amount` tokens at up to `max_price`
+    /// stroops per whole token
+    ///
+    /// The order first fills against resting asks at `max_price` or lower,
+    /// best price first. Depending on `time_in_force` any remainder rests on
+    /// the book, until `expires_at` for good-til-time orders, with the XLM to
+    /// pay for it held in escrow by the DEX until it is filled, cancelled or
+    /// purged. An order that fills `MAX_FILLS` asks while more still cross
+    /// its price drops its remainder like an immediate-or-cancel order.
+    pub fn create_buy_order(
+        env: Env,
+        buyer: Address,
+        token_address: Address,
+        amount: i128,
+        max_price: i128,
+        time_in_force: TimeInForce,
+        expires_at: u64,
+    ) -> Result<FillSummary, Error> {
+        buyer.require_auth();
+        Self::check_order(amount, max_price)?;
+        let expires_at = Self::check_expiry(&env, time_in_force, expires_at)?;
+        
+        extend_instance(&env);
+        let mut summary =
+            Self::match_orders(&env, &buyer, &token_address, Side::Sell, amount, max_price)?;
         
-        // Get or initialize order count
-        let order_count: u64 = env.storage()
-            .instance()
-            .get(&count_key)
-            .unwrap_or(0);
+        let remainder = amount - summary.filled;
+        if Self::rests(time_in_force, remainder)?
+            && !Self::cut_short(&env, &summary, &token_address, Side::Sell, max_price)
+        {
+            let order_id = Self::open_order(
+                &env,
+                &buyer,
+                &token_address,
+                Side::Buy,
+                remainder,
+                max_price,
+                expires_at,
+            )?;
+            summary.order_id = Some(order_id);
+            
+            // Escrow the XLM bid for the remainder
+            let escrow = Self::bid_escrow(&env, remainder, max_price)?;
+            token::Client::new(&env, &get_xlm_token(&env)?).transfer(
+                &buyer,
+                &env.current_contract_address(),
+                &escrow,
+            );
+        }
+        
+        Ok(summary)
+    }
+    
+    /// Buy up to `amount` tokens from the resting asks priced at or below
+    /// `limit_price`, best price first. Nothing rests on the book.
+    pub fn market_buy(
+        env: Env,
+        buyer: Address,
+        token_address: Address,
+        amount: i128,
+        limit_price: i128,
+    ) -> Result<FillSummary, Error> {
+        buyer.require_auth();
+        Self::check_order(amount, limit_price)?;
         
-        let order_id = order_count + 1;
+        extend_instance(&env);
+        Self::match_orders(&env, &buyer, &token_address, Side::Sell, amount, limit_price)
+    }
+    
+    /// Sell up to `amount` tokens into the resting bids priced at or above
+    /// `limit_price`, best price first. Nothing rests on the book.
+    pub fn market_sell(
+        env: Env,
+        seller: Address,
+        token_address: Address,
+        amount: i128,
+        limit_price: i128,
+    ) -> Result<FillSummary, Error> {
+        seller.require_auth();
+        Self::check_order(amount, limit_price)?;
         
-        // Create order
-        let order = SellOrder {
+        extend_instance(&env);
+        Self::match_orders(&env, &seller, &token_address, Side::Buy, amount, limit_price)
+    }
+    
+    /// Internal: Reject unusable order amounts and prices
+    fn check_order(amount: i128, price: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if price <= 0 {
+            return Err(Error::InvalidPrice);
+        }
+        Ok(())
+    }
+    
+    /// Internal: Expiry to store for an order, zero unless it is good til
+    /// a time still in the future
+    fn check_expiry(env: &Env, time_in_force: TimeInForce, expires_at: u64) -> Result<u64, Error> {
+        if time_in_force != TimeInForce::GoodTilTime {
+            return Ok(0);
+        }
+        if expires_at <= env.ledger().timestamp() {
+            return Err(Error::InvalidExpiry);
+        }
+        Ok(expires_at)
+    }
+    
+    /// Internal: Whether the unfilled `remainder` of an order rests on the
+    /// book. Fill-or-kill orders fail, undoing their fills, if any remains.
+    fn rests(time_in_force: TimeInForce, remainder: i128) -> Result<bool, Error> {
+        match time_in_force {
+            TimeInForce::FillOrKill if remainder > 0 => Err(Error::OrderNotFilled),
+            TimeInForce::FillOrKill | TimeInForce::ImmediateOrCancel => Ok(false),
+            TimeInForce::GoodTilCancelled | TimeInForce::GoodTilTime => Ok(remainder > 0),
+        }
+    }
+    
+    /// Internal: Whether a taker that stopped at `MAX_FILLS` left live
+    /// orders on `side` crossing `limit_price`. Its remainder must not rest
+    /// then or the book would be crossed.
+    fn cut_short(
+        env: &Env,
+        summary: &FillSummary,
+        token_address: &Address,
+        side: Side,
+        limit_price: i128,
+    ) -> bool {
+        if summary.trades < MAX_FILLS {
+            return false;
+        }
+        match Self::price_levels(env, token_address, side, 1).first() {
+            Some(level) => Self::crosses(side, level.price, limit_price),
+            None => false,
+        }
+    }
+    
+    /// Internal: Whether a resting order on `side` at `price` fills a taker
+    /// limited to `limit_price`
+    fn crosses(side: Side, price: i128, limit_price: i128) -> bool {
+        match side {
+            Side::Sell => price <= limit_price,
+            Side::Buy => price >= limit_price,
+        }
+    }
+    
+    /// Internal: Store and index a new resting order. Fails if it is too
+    /// small or its price level is full. A new price on a full side of the
+    /// book evicts the worst priced level if it beats it and fails if not.
+    fn open_order(
+        env: &Env,
+        owner: &Address,
+        token_address: &Address,
+        side: Side,
+        amount: i128,
+        price: i128,
+        expires_at: u64,
+    ) -> Result<u64, Error> {
+        if amount < get_min_order_amount(env)
+            || Self::bid_escrow(env, amount, price)? < get_min_order_value(env)
+        {
+            return Err(Error::OrderTooSmall);
+        }
+        let level_orders = get_level_orders(env, token_address, side, price).len();
+        if level_orders >= MAX_LEVEL_ORDERS {
+            return Err(Error::PriceLevelFull);
+        }
+        if level_orders == 0
+            && get_price_levels(env, token_address, side).len() >= MAX_PRICE_LEVELS
+        {
+            Self::evict_worst(env, token_address, side, price)?;
+        }
+        
+        let order_id = get_order_counter(env) + 1;
+        
+        let order = Order {
             order_id,
-            seller: seller.clone(),
-            token_contract: token_contract.clone(),
+            owner: owner.clone(),
+            token_address: token_address.clone(),
+            side,
             amount,
-            price_per_token,
+            price,
+            status: OrderStatus::Open,
             created_at: env.ledger().timestamp(),
-            is_active: true,
+            expires_at,
+        };
+        
+        // Store order and index it by owner and price level
+        set_order(env, &order);
+        set_order_counter(env, order_id);
+        add_user_order(env, owner, order_id);
+        add_book_order(env, &order);
+        
+        env.events().publish(
+            (symbol_short!("ORDER"), token_address.clone()),
+            (order_id, owner.clone(), side, amount, price),
+        );
+        
+        Ok(order_id)
+    }
+    
+    /// Internal: Cancel every order at the worst price on a full side of
+    /// the book, refunding their owners, to make room for a level at
+    /// `price`. Fails if `price` does not beat the worst price.
+    fn evict_worst(
+        env: &Env,
+        token_address: &Address,
+        side: Side,
+        price: i128,
+    ) -> Result<(), Error> {
+        let worst = match get_price_levels(env, token_address, side).last() {
+            Some(worst) => worst,
+            None => return Ok(()),
         };
+        let better = match side {
+            Side::Buy => price > worst,
+            Side::Sell => price < worst,
+        };
+        if !better {
+            return Err(Error::OrderBookFull);
+        }
+        
+        let now = env.ledger().timestamp();
+        for order_id in get_level_orders(env, token_address, side, worst).iter() {
+            let mut order = match get_order(env, order_id) {
+                Some(order) => order,
+                None => continue,
+            };
+            order.status = if order.is_expired(now) {
+                OrderStatus::Expired
+            } else {
+                OrderStatus::Cancelled
+            };
+            set_order(env, &order);
+            Self::close(env, &order);
+            Self::refund(env, &order)?;
+            
+            env.events().publish(
+                (symbol_short!("EVICT"), token_address.clone()),
+                (order_id, order.owner),
+            );
+        }
+        
+        Ok(())
+    }
+    
+    /// Internal: Fill up to `amount` tokens for `taker` against the resting
+    /// orders on `side` priced within `limit_price`, in price-time priority.
+    /// At most `MAX_FILLS` orders are filled, the rest is left unfilled.
+    fn match_orders(
+        env: &Env,
+        taker: &Address,
+        token_address: &Address,
+        side: Side,
+        amount: i128,
+        limit_price: i128,
+    ) -> Result<FillSummary, Error> {
+        let mut summary = FillSummary {
+            filled: 0,
+            total: 0,
+            avg_price: 0,
+            fee: 0,
+            trades: 0,
+            order_id: None,
+        };
+        
+        // Walk price levels best first and stop at the first one not crossing
+        for price in get_price_levels(env, token_address, side).iter() {
+            let crosses = Self::crosses(side, price, limit_price);
+            if summary.filled == amount || summary.trades == MAX_FILLS || !crosses {
+                break;
+            }
+            
+            for mut order in Self::level_orders(env, token_address, side, price).iter() {
+                if summary.filled == amount || summary.trades == MAX_FILLS {
+                    break;
+                }
+                
+                let fill = order.amount.min(amount - summary.filled);
+                Self::take(env, &mut order, fill);
+                let total = Self::fill_total(env, &order, fill)?;
+                Self::record_trade(env, &order, taker, fill, total);
+                
+                // The maker's side settles per fill
+                Self::pay_maker(env, &order, taker, fill, total)?;
+                
+                summary.filled += fill;
+                summary.total += total;
+                summary.trades += 1;
+            }
+        }
         
-        // Get or initialize orders map
-        let mut orders: Map<u64, SellOrder> = env.storage()
-            .instance()
-            .get(&orders_key)
-            .unwrap_or(Map::new(&env));
+        // The taker's side settles out of escrow at once
+        if summary.filled > 0 {
+            summary.fee = Self::pay_taker(
+                env,
+                taker,
+                token_address,
+                side,
+                summary.filled,
+                summary.total,
+            )?;
+            summary.avg_price =
+                mul_div(env, summary.total, PRICE_SCALE, summary.filled, Rounding::Down)?;
+        }
         
-        // Store order
-        orders.set(order_id, order);
-        env.storage().instance().set(&orders_key, &orders);
-        env.storage().instance().set(&count_key, &order_id);
+        Ok(summary)
+    }
+    
+    /// Buy `amount` tokens from a sell order, returns the trade id
+    ///
+    /// The buyer pays the seller in XLM and receives the tokens from escrow,
+    /// each less their fee.
+    pub fn buy_tokens(env: Env, buyer: Address, order_id: u64, amount: i128) -> Result<u64, Error> {
+        buyer.require_auth();
+        
+        let order = Self::fill(&env, order_id, Side::Sell, amount)?;
+        
+        let total = Self::fill_total(&env, &order, amount)?;
+        let trade_id = Self::record_trade(&env, &order, &buyer, amount, total);
         
-        order_id
+        // Settle: XLM from buyer to seller, tokens out of escrow to buyer
+        Self::pay_maker(&env, &order, &buyer, amount, total)?;
+        Self::pay_taker(&env, &buyer, &order.token_address, Side::Sell, amount, total)?;
+        
+        Ok(trade_id)
     }
     
-    /// Buy tokens from a sell order
-    pub fn buy_tokens(
+    /// Sell `amount` tokens into a buy order, returns the trade id
+    ///
+    /// The seller is paid from the order's XLM escrow and the tokens go
+    /// straight to the bidder, each less their fee.
+    pub fn sell_tokens(
         env: Env,
-        buyer: Address,
+        seller: Address,
         order_id: u64,
         amount: i128,
-    ) -> bool {
-        buyer.require_auth();
+    ) -> Result<u64, Error> {
+        seller.require_auth();
         
-        let orders_key = symbol_short!("ORDERS");
+        let order = Self::fill(&env, order_id, Side::Buy, amount)?;
         
-        let mut orders: Map<u64, SellOrder> = env.storage()
-            .instance()
-            .get(&orders_key)
-            .unwrap();
+        let total = Self::fill_total(&env, &order, amount)?;
+        let trade_id = Self::record_trade(&env, &order, &seller, amount, total);
+        
+        // Settle: tokens from seller to bidder, XLM out of escrow to seller
+        Self::pay_maker(&env, &order, &seller, amount, total)?;
+        Self::pay_taker(&env, &seller, &order.token_address, Side::Buy, amount, total)?;
+        
+        Ok(trade_id)
+    }
+    
+    /// Internal: Pay the owner of `order` for a fill of `amount` tokens for
+    /// `total` XLM out of the taker's funds, keeping the maker fee
+    fn pay_maker(
+        env: &Env,
+        order: &Order,
+        taker: &Address,
+        amount: i128,
+        total: i128,
+    ) -> Result<(), Error> {
+        let (asset, proceeds) = match order.side {
+            Side::Sell => (get_xlm_token(env)?, total),
+            Side::Buy => (order.token_address.clone(), amount),
+        };
+        let fee = Self::charge(env, &order.owner, true, &asset, proceeds)?;
         
-        let mut order = orders.get(order_id).unwrap();
+        let client = token::Client::new(env, &asset);
+        client.transfer(taker, &order.owner, &(proceeds - fee));
+        if fee > 0 {
+            client.transfer(taker, &env.current_contract_address(), &fee);
+        }
+        
+        Self::add_volume(env, &order.owner, total);
+        Ok(())
+    }
+    
+    /// Internal: Pay `taker` out of escrow for `amount` tokens taken from
+    /// orders on `side` for `total` XLM, returns the taker fee kept
+    fn pay_taker(
+        env: &Env,
+        taker: &Address,
+        token_address: &Address,
+        side: Side,
+        amount: i128,
+        total: i128,
+    ) -> Result<i128, Error> {
+        let (asset, proceeds) = match side {
+            Side::Sell => (token_address.clone(), amount),
+            Side::Buy => (get_xlm_token(env)?, total),
+        };
+        let fee = Self::charge(env, taker, false, &asset, proceeds)?;
+        
+        token::Client::new(env, &asset).transfer(
+            &env.current_contract_address(),
+            taker,
+            &(proceeds - fee),
+        );
+        
+        Self::add_volume(env, taker, total);
+        Ok(fee)
+    }
+    
+    /// Internal: Fee `trader` owes as maker or taker on `amount` of `asset`
+    /// received, at the tier their volume has reached. Credited to the
+    /// treasury's balance of `asset`.
+    fn charge(
+        env: &Env,
+        trader: &Address,
+        maker: bool,
+        asset: &Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        let (maker_fee_bps, taker_fee_bps) =
+            get_fee_config(env)?.rates(get_trader_volume(env, trader));
+        let fee_bps = if maker { maker_fee_bps } else { taker_fee_bps };
         
-        // Verify order is active
-        if !order.is_active {
-            panic!("Order is not active");
+        let fee = fees::fee_on(env, amount, fee_bps)?;
+        if fee > 0 {
+            set_accrued_fees(env, asset, get_accrued_fees(env, asset) + fee);
         }
+        Ok(fee)
+    }
+    
+    /// Internal: Count `total` XLM towards `trader`'s fee tier
+    fn add_volume(env: &Env, trader: &Address, total: i128) {
+        set_trader_volume(env, trader, get_trader_volume(env, trader) + total);
+    }
+    
+    /// Internal: Take `amount` from an open order on `side`, returns the
+    /// updated order
+    fn fill(env: &Env, order_id: u64, side: Side, amount: i128) -> Result<Order, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        extend_instance(env);
+        let mut order = get_order(env, order_id).ok_or(Error::OrderNotFound)?;
         
+        // Verify order is open
+        if order.status != OrderStatus::Open {
+            return Err(Error::OrderNotActive);
+        }
+        if order.side != side {
+            return Err(Error::WrongOrderSide);
+        }
+        if order.is_expired(env.ledger().timestamp()) {
+            return Err(Error::OrderExpired);
+        }
         if amount > order.amount {
-            panic!("Insufficient tokens in order");
+            return Err(Error::InsufficientOrderAmount);
         }
         
-        // Update order amount
+        Self::take(env, &mut order, amount);
+        Ok(order)
+    }
+    
+    /// Internal: Reduce an order by `amount`, filled orders leave the book
+    fn take(env: &Env, order: &mut Order, amount: i128) {
         order.amount -= amount;
         if order.amount == 0 {
-            order.is_active = false;
+            order.status = OrderStatus::Filled;
+            Self::close(env, order);
         }
+        set_order(env, order);
+    }
+    
+    /// Internal: Take a filled, cancelled or expired order off the book and
+    /// file it in the owner's history under its new status
+    fn close(env: &Env, order: &Order) {
+        remove_book_order(env, order);
+        remove_user_order(env, &order.owner, order.order_id);
+        add_closed_order(env, &order.owner, &order.status, order.order_id);
+    }
+    
+    /// Internal: XLM changing hands for `amount` tokens just taken from
+    /// `order`. Asks are paid rounded up, bids release their escrow so fills
+    /// add up to exactly what was escrowed.
+    fn fill_total(env: &Env, order: &Order, amount: i128) -> Result<i128, Error> {
+        match order.side {
+            Side::Sell => Ok(mul_div(env, amount, order.price, PRICE_SCALE, Rounding::Up)?),
+            Side::Buy => {
+                let before = Self::bid_escrow(env, order.amount + amount, order.price)?;
+                Ok(before - Self::bid_escrow(env, order.amount, order.price)?)
+            }
+        }
+    }
+    
+    /// Internal: XLM escrowed for `amount` unfilled tokens of a buy order at
+    /// `price`, rounded up
+    fn bid_escrow(env: &Env, amount: i128, price: i128) -> Result<i128, Error> {
+        Ok(mul_div(env, amount, price, PRICE_SCALE, Rounding::Up)?)
+    }
+    
+    /// Cancel an order and return its unfilled tokens or XLM from escrow
+    pub fn cancel_order(env: Env, owner: Address, order_id: u64) -> Result<(), Error> {
+        owner.require_auth();
         
-        // Save updated order
-        orders.set(order_id, order);
-        env.storage().instance().set(&orders_key, &orders);
+        extend_instance(&env);
+        let mut order = get_order(&env, order_id).ok_or(Error::OrderNotFound)?;
         
-        true
+        // Verify caller owns the order
+        if order.owner != owner {
+            return Err(Error::NotOrderOwner);
+        }
+        if order.status != OrderStatus::Open {
+            return Err(Error::OrderNotActive);
+        }
+        
+        order.status = OrderStatus::Cancelled;
+        set_order(&env, &order);
+        Self::close(&env, &order);
+        Self::refund(&env, &order)?;
+        
+        env.events().publish(
+            (symbol_short!("CANCEL"), order.token_address),
+            (order_id, owner),
+        );
+        
+        Ok(())
     }
     
-    /// Cancel a sell order
-    pub fn cancel_order(env: Env, seller: Address, order_id: u64) -> bool {
-        seller.require_auth();
+    /// Sweep up to `limit` orders for expired ones, returning their escrow
+    /// to the owners, returns how many were purged
+    ///
+    /// Anyone may call this. Each call carries on from where the last one
+    /// stopped and wraps around after the newest order, `limit` is capped
+    /// at `MAX_PURGE`. Purged orders leave the book and are filed in their
+    /// owner's history as expired.
+    pub fn purge_expired(env: Env, limit: u32) -> Result<u32, Error> {
+        extend_instance(&env);
+        let now = env.ledger().timestamp();
+        let limit = if limit > MAX_PURGE { MAX_PURGE } else { limit };
         
-        let orders_key = symbol_short!("ORDERS");
+        let counter = get_order_counter(&env);
+        let cursor = get_purge_cursor(&env);
+        let end = counter.min(cursor + limit as u64);
         
-        let mut orders: Map<u64, SellOrder> = env.storage()
-            .instance()
-            .get(&orders_key)
-            .unwrap();
+        let mut purged = 0;
+        for order_id in cursor + 1..=end {
+            let mut order = match get_order(&env, order_id) {
+                Some(order) => order,
+                None => continue,
+            };
+            if order.status != OrderStatus::Open || !order.is_expired(now) {
+                continue;
+            }
+            
+            order.status = OrderStatus::Expired;
+            set_order(&env, &order);
+            Self::close(&env, &order);
+            Self::refund(&env, &order)?;
+            purged += 1;
+            
+            env.events().publish(
+                (symbol_short!("EXPIRE"), order.token_address),
+                (order_id, order.owner),
+            );
+        }
+        
+        set_purge_cursor(&env, if end >= counter { 0 } else { end });
+        Ok(purged)
+    }
+    
+    /// Internal: Return an open order's unfilled tokens or XLM from escrow
+    fn refund(env: &Env, order: &Order) -> Result<(), Error> {
+        let (escrow_token, refund) = match order.side {
+            Side::Sell => (order.token_address.clone(), order.amount),
+            Side::Buy => (get_xlm_token(env)?, Self::bid_escrow(env, order.amount, order.price)?),
+        };
+        token::Client::new(env, &escrow_token).transfer(
+            &env.current_contract_address(),
+            &order.owner,
+            &refund,
+        );
+        Ok(())
+    }
+    
+    /// Internal: Record a fill of `amount` tokens from `order` for `total` XLM
+    fn record_trade(env: &Env, order: &Order, taker: &Address, amount: i128, total: i128) -> u64 {
+        let trade_id = get_trade_counter(env) + 1;
+        let (buyer, seller) = match order.side {
+            Side::Sell => (taker.clone(), order.owner.clone()),
+            Side::Buy => (order.owner.clone(), taker.clone()),
+        };
+        
+        let trade = Trade {
+            trade_id,
+            order_id: order.order_id,
+            buyer,
+            seller,
+            token_address: order.token_address.clone(),
+            amount,
+            price: order.price,
+            total,
+            timestamp: env.ledger().timestamp(),
+        };
+        
+        set_trade(env, &trade);
+        set_trade_counter(env, trade_id);
+        
+        env.events().publish(
+            (symbol_short!("FILL"), order.token_address.clone()),
+            (trade_id, order.order_id, taker.clone(), amount, total),
+        );
+        
+        trade_id
+    }
+    
+    /// Get both sides of a token's book, best price first and oldest first
+    /// within a price
+    pub fn get_order_book(env: Env, token_address: Address) -> OrderBook {
+        OrderBook {
+            bids: Self::sorted_orders(&env, &token_address, Side::Buy),
+            asks: Self::sorted_orders(&env, &token_address, Side::Sell),
+        }
+    }
+    
+    /// Get the highest bid and lowest ask of a token
+    pub fn get_best_bid_ask(env: Env, token_address: Address) -> BestBidAsk {
+        let best = |side| Self::price_levels(&env, &token_address, side, 1).first();
+        BestBidAsk {
+            bid: best(Side::Buy).map(|level| level.price),
+            ask: best(Side::Sell).map(|level| level.price),
+        }
+    }
+    
+    /// Get up to `levels` price levels on each side of a token's book with
+    /// the amount resting at each, best price first
+    pub fn get_depth(env: Env, token_address: Address, levels: u32) -> Depth {
+        Depth {
+            bids: Self::price_levels(&env, &token_address, Side::Buy, levels),
+            asks: Self::price_levels(&env, &token_address, Side::Sell, levels),
+        }
+    }
+    
+    /// Internal: Up to `limit` price levels of a token on `side` that still
+    /// hold live orders, best price first
+    fn price_levels(env: &Env, token_address: &Address, side: Side, limit: u32) -> Vec<PriceLevel> {
+        let mut levels = Vec::new(env);
+        
+        for price in get_price_levels(env, token_address, side).iter() {
+            if levels.len() == limit {
+                break;
+            }
+            
+            let mut level = PriceLevel {
+                price,
+                amount: 0,
+                orders: 0,
+            };
+            for order in Self::level_orders(env, token_address, side, price).iter() {
+                level.amount += order.amount;
+                level.orders += 1;
+            }
+            if level.orders > 0 {
+                levels.push_back(level);
+            }
+        }
         
-        let mut order = orders.get(order_id).unwrap();
+        levels
+    }
+    
+    /// Internal: Live orders of a token on `side` in price-time priority,
+    /// read level by level from the price index
+    fn sorted_orders(env: &Env, token_address: &Address, side: Side) -> Vec<Order> {
+        let mut book = Vec::new(env);
         
-        // Verify seller owns the order
-        if order.seller != seller {
-            panic!("Not order owner");
+        for price in get_price_levels(env, token_address, side).iter() {
+            book.append(&Self::level_orders(env, token_address, side, price));
         }
         
-        // Mark as inactive
-        order.is_active = false;
-        orders.set(order_id, order);
-        env.storage().instance().set(&orders_key, &orders);
+        book
+    }
+    
+    /// Internal: Live orders resting at one price level, oldest first.
+    /// Expired orders are left out until they are purged.
+    fn level_orders(env: &Env, token_address: &Address, side: Side, price: i128) -> Vec<Order> {
+        let now = env.ledger().timestamp();
+        let mut orders = Vec::new(env);
+        
+        let order_ids = get_level_orders(env, token_address, side, price);
+        for order in Self::load_orders(env, order_ids).iter() {
+            if !order.is_expired(now) {
+                orders.push_back(order);
+            }
+        }
         
-        true
+        orders
     }
     
-    /// Get all orders for a token
-    pub fn get_token_orders(
+    /// Get open orders for a token, bids then asks, each in price-time
+    /// priority
+    pub fn get_token_orders(env: Env, token_address: Address) -> Vec<Order> {
+        let mut orders = Self::sorted_orders(&env, &token_address, Side::Buy);
+        orders.append(&Self::sorted_orders(&env, &token_address, Side::Sell));
+        orders
+    }
+    
+    /// Get up to `limit` of a user's orders with `status`, skipping the
+    /// first `start`. Open orders are listed oldest first, closed orders in
+    /// the order they closed. `limit` is capped at `MAX_PAGE_SIZE`.
+    pub fn get_user_orders(
         env: Env,
-        token_contract: Address,
-    ) -> Vec<SellOrder> {
-        let orders_key = symbol_short!("ORDERS");
-        
-        let orders: Map<u64, SellOrder> = env.storage()
-            .instance()
-            .get(&orders_key)
-            .unwrap_or(Map::new(&env));
-        
-        let mut result = Vec::new(&env);
-        
-        let keys = orders.keys();
-        for i in 0..keys.len() {
-            if let Some(key) = keys.get(i) {
-                if let Some(order) = orders.get(key) {
-                    if order.token_contract == token_contract && order.is_active {
-                        result.push_back(order);
-                    }
-                }
+        user: Address,
+        status: OrderStatus,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Order> {
+        let limit = if limit > MAX_PAGE_SIZE { MAX_PAGE_SIZE } else { limit };
+        
+        if status == OrderStatus::Open {
+            let order_ids = get_user_orders(&env, &user);
+            let end = order_ids.len().min(start.saturating_add(limit));
+            if start >= end {
+                return Vec::new(&env);
+            }
+            return Self::load_orders(&env, order_ids.slice(start..end));
+        }
+        
+        let mut order_ids = Vec::new(&env);
+        let end = get_closed_count(&env, &user, &status).min(start.saturating_add(limit));
+        for index in start..end {
+            if let Some(order_id) = get_closed_order(&env, &user, &status, index) {
+                order_ids.push_back(order_id);
+            }
+        }
+        Self::load_orders(&env, order_ids)
+    }
+    
+    /// Internal: Look up orders by id
+    fn load_orders(env: &Env, order_ids: Vec<u64>) -> Vec<Order> {
+        let mut orders = Vec::new(env);
+        
+        for order_id in order_ids.iter() {
+            if let Some(order) = get_order(env, order_id) {
+                orders.push_back(order);
             }
         }
         
-        result
+        orders
     }
     
     /// Get total order count
     pub fn get_order_count(env: Env) -> u64 {
-        let count_key = symbol_short!("ORD_CNT");
-        env.storage().instance().get(&count_key).unwrap_or(0)
+        get_order_counter(&env)
     }
     
     /// Get a specific order by ID
-    pub fn get_order(env: Env, order_id: u64) -> Option<SellOrder> {
-        let orders_key = symbol_short!("ORDERS");
-        
-        let orders: Map<u64, SellOrder> = env.storage()
-            .instance()
-            .get(&orders_key)
-            .unwrap_or(Map::new(&env));
-        
-        orders.get(order_id)
+    pub fn get_order(env: Env, order_id: u64) -> Option<Order> {
+        get_order(&env, order_id)
+    }
+    
+    /// Get total trade count
+    pub fn get_trade_count(env: Env) -> u64 {
+        get_trade_counter(&env)
+    }
+    
+    /// Get a specific trade by ID
+    pub fn get_trade(env: Env, trade_id: u64) -> Option<Trade> {
+        get_trade(&env, trade_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::StellarAssetClient,
+        Env,
+    };
+
+    const GTC: TimeInForce = TimeInForce::GoodTilCancelled;
+    const GTT: TimeInForce = TimeInForce::GoodTilTime;
+    const IOC: TimeInForce = TimeInForce::ImmediateOrCancel;
+    const FOK: TimeInForce = TimeInForce::FillOrKill;
+
+    fn setup(e: &Env) -> (DEXClient<'_>, Address, Address) {
+        e.mock_all_auths();
+
+        let contract_id = e.register_contract(None, DEX);
+        let client = DEXClient::new(e, &contract_id);
+
+        let xlm = e
+            .register_stellar_asset_contract_v2(Address::generate(e))
+            .address();
+        let meme = e
+            .register_stellar_asset_contract_v2(Address::generate(e))
+            .address();
+
+        client.initialize(&Address::generate(e), &xlm);
+        // Most tests rest orders far smaller than the default minimums
+        client.set_min_order_value(&0);
+        client.set_min_order_amount(&0);
+        (client, xlm, meme)
+    }
+
+    fn ids(e: &Env, orders: &Vec<Order>) -> Vec<u64> {
+        let mut ids = Vec::new(e);
+        for order in orders.iter() {
+            ids.push_back(order.order_id);
+        }
+        ids
+    }
+
+    #[test]
+    fn test_fills_settle_from_escrow() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let seller = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &30_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &15_000_000);
+
+        let order_id = client
+            .create_sell_order(&seller, &meme, &30_000_000, &5_000_000, &GTC, &0)
+            .order_id
+            .unwrap();
+        assert_eq!(meme_client.balance(&seller), 0);
+        assert_eq!(meme_client.balance(&client.address), 30_000_000);
+        assert_eq!(client.get_token_orders(&meme).len(), 1);
+
+        let trade_id = client.buy_tokens(&buyer, &order_id, &10_000_000);
+        let trade = client.get_trade(&trade_id).unwrap();
+        assert_eq!(trade.order_id, order_id);
+        assert_eq!(trade.seller, seller);
+        assert_eq!(trade.total, 5_000_000);
+        assert_eq!(client.get_order(&order_id).unwrap().amount, 20_000_000);
+        assert_eq!(xlm_client.balance(&seller), 5_000_000);
+        assert_eq!(meme_client.balance(&buyer), 10_000_000);
+
+        client.buy_tokens(&buyer, &order_id, &20_000_000);
+        assert_eq!(client.get_trade_count(), 2);
+        assert_eq!(client.get_order(&order_id).unwrap().status, OrderStatus::Filled);
+        assert_eq!(client.get_token_orders(&meme).len(), 0);
+        assert_eq!(client.get_user_orders(&seller, &OrderStatus::Filled, &0, &10).len(), 1);
+        assert_eq!(xlm_client.balance(&buyer), 0);
+        assert_eq!(meme_client.balance(&client.address), 0);
+
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &order_id, &1),
+            Err(Ok(Error::OrderNotActive))
+        );
+    }
+
+    #[test]
+    fn test_cancel_order_refunds_escrow() {
+        let e = Env::default();
+        let (client, _, meme) = setup(&e);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let seller = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &1_000);
+        let order_id =
+            client.create_sell_order(&seller, &meme, &1_000, &1_000, &GTC, &0).order_id.unwrap();
+
+        assert_eq!(
+            client.try_cancel_order(&Address::generate(&e), &order_id),
+            Err(Ok(Error::NotOrderOwner))
+        );
+
+        client.cancel_order(&seller, &order_id);
+        assert_eq!(client.get_order(&order_id).unwrap().status, OrderStatus::Cancelled);
+        assert_eq!(client.get_token_orders(&meme).len(), 0);
+        assert_eq!(meme_client.balance(&seller), 1_000);
+        assert_eq!(
+            client.try_cancel_order(&seller, &order_id),
+            Err(Ok(Error::OrderNotActive))
+        );
+    }
+
+    #[test]
+    fn test_bids_settle_from_xlm_escrow() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let bidder = Address::generate(&e);
+        let seller = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&bidder, &20);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &3);
+
+        // Just over a third of a stroop per unit, rounded up to 2 stroops for 3
+        let order_id =
+            client.create_buy_order(&bidder, &meme, &3, &3_333_334, &GTC, &0).order_id.unwrap();
+        assert_eq!(xlm_client.balance(&client.address), 2);
+        assert_eq!(
+            client.try_buy_tokens(&seller, &order_id, &1),
+            Err(Ok(Error::WrongOrderSide))
+        );
+
+        for _ in 0..3 {
+            client.sell_tokens(&seller, &order_id, &1);
+        }
+        assert_eq!(meme_client.balance(&bidder), 3);
+        assert_eq!(xlm_client.balance(&seller), 2);
+        assert_eq!(xlm_client.balance(&client.address), 0);
+        assert_eq!(client.get_trade(&3).unwrap().buyer, bidder);
+
+        let order_id =
+            client.create_buy_order(&bidder, &meme, &10, &PRICE_SCALE, &GTC, &0).order_id.unwrap();
+        client.cancel_order(&bidder, &order_id);
+        assert_eq!(xlm_client.balance(&bidder), 18);
+    }
+
+    #[test]
+    fn test_order_book_sorted_by_price_then_time() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let trader = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&trader, &1_000_000_000);
+        StellarAssetClient::new(&e, &meme).mint(&trader, &1_000_000_000);
+
+        let bid_low =
+            client.create_buy_order(&trader, &meme, &10, &100, &GTC, &0).order_id.unwrap();
+        let bid_high =
+            client.create_buy_order(&trader, &meme, &10, &200, &GTC, &0).order_id.unwrap();
+        let bid_low_later =
+            client.create_buy_order(&trader, &meme, &10, &100, &GTC, &0).order_id.unwrap();
+        let ask_high =
+            client.create_sell_order(&trader, &meme, &10, &500, &GTC, &0).order_id.unwrap();
+        let ask_low =
+            client.create_sell_order(&trader, &meme, &10, &300, &GTC, &0).order_id.unwrap();
+
+        let book = client.get_order_book(&meme);
+        assert_eq!(ids(&e, &book.bids), soroban_sdk::vec![&e, bid_high, bid_low, bid_low_later]);
+        assert_eq!(ids(&e, &book.asks), soroban_sdk::vec![&e, ask_low, ask_high]);
+    }
+
+    #[test]
+    fn test_market_buy_walks_asks_in_price_time_priority() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let early = Address::generate(&e);
+        let late = Address::generate(&e);
+        let cheap = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        for maker in [&early, &late, &cheap] {
+            StellarAssetClient::new(&e, &meme).mint(maker, &10_000_000);
+        }
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &100_000_000);
+
+        let early_id = client
+            .create_sell_order(&early, &meme, &10_000_000, &3_000_000, &GTC, &0)
+            .order_id
+            .unwrap();
+        let late_id = client
+            .create_sell_order(&late, &meme, &10_000_000, &3_000_000, &GTC, &0)
+            .order_id
+            .unwrap();
+        client.create_sell_order(&cheap, &meme, &10_000_000, &2_000_000, &GTC, &0);
+
+        // Cheapest ask first, then the older of the two asks at the same price
+        let summary = client.market_buy(&buyer, &meme, &25_000_000, &3_000_000);
+        assert_eq!(summary.filled, 25_000_000);
+        assert_eq!(summary.total, 6_500_000);
+        assert_eq!(summary.avg_price, 2_600_000);
+        assert_eq!(summary.trades, 3);
+        assert_eq!(summary.order_id, None);
+        assert_eq!(meme_client.balance(&buyer), 25_000_000);
+        assert_eq!(xlm_client.balance(&cheap), 2_000_000);
+        assert_eq!(xlm_client.balance(&early), 3_000_000);
+        assert_eq!(client.get_order(&early_id).unwrap().status, OrderStatus::Filled);
+        assert_eq!(client.get_order(&late_id).unwrap().amount, 5_000_000);
+
+        // Nothing left at or below the limit
+        let summary = client.market_buy(&buyer, &meme, &1_000_000, &2_999_999);
+        assert_eq!(summary.filled, 0);
+        assert_eq!(client.get_trade_count(), 3);
+    }
+
+    #[test]
+    fn test_limit_sell_matches_bids_then_rests() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let bidder = Address::generate(&e);
+        let seller = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&bidder, &10_000_000);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &30_000_000);
+
+        client.create_buy_order(&bidder, &meme, &10_000_000, &4_000_000, &GTC, &0);
+        client.create_buy_order(&bidder, &meme, &10_000_000, &1_000_000, &GTC, &0);
+
+        let summary = client.create_sell_order(&seller, &meme, &30_000_000, &2_000_000, &GTC, &0);
+        assert_eq!(summary.filled, 10_000_000);
+        assert_eq!(summary.total, 4_000_000);
+        assert_eq!(meme_client.balance(&bidder), 10_000_000);
+        assert_eq!(xlm_client.balance(&seller), 4_000_000);
+        assert_eq!(meme_client.balance(&client.address), 20_000_000);
+
+        let book = client.get_order_book(&meme);
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!(book.asks.get(0).unwrap().order_id, summary.order_id.unwrap());
+        assert_eq!(book.asks.get(0).unwrap().amount, 20_000_000);
+    }
+
+    #[test]
+    fn test_expired_orders_stop_filling_and_purge_refunds() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let seller = Address::generate(&e);
+        let bidder = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &10_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&bidder, &1_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &10_000_000);
+
+        assert_eq!(
+            client.try_create_sell_order(&seller, &meme, &10_000_000, &1_000_000, &GTT, &0),
+            Err(Ok(Error::InvalidExpiry))
+        );
+
+        let ask_id = client
+            .create_sell_order(&seller, &meme, &10_000_000, &1_000_000, &GTT, &100)
+            .order_id
+            .unwrap();
+        let bid_id = client
+            .create_buy_order(&bidder, &meme, &10_000_000, &100_000, &GTT, &200)
+            .order_id
+            .unwrap();
+        assert_eq!(client.get_order(&ask_id).unwrap().expires_at, 100);
+
+        e.ledger().set_timestamp(100);
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &ask_id, &1_000_000),
+            Err(Ok(Error::OrderExpired))
+        );
+        let summary = client.market_buy(&buyer, &meme, &1_000_000, &1_000_000);
+        assert_eq!(summary.filled, 0);
+        assert_eq!(client.get_order_book(&meme).asks.len(), 0);
+        assert_eq!(client.get_order_book(&meme).bids.len(), 1);
+
+        // Only the ask has expired so far
+        assert_eq!(client.purge_expired(&10), 1);
+        assert_eq!(client.get_order(&ask_id).unwrap().status, OrderStatus::Expired);
+        assert_eq!(client.get_user_orders(&seller, &OrderStatus::Open, &0, &10).len(), 0);
+        let expired = client.get_user_orders(&seller, &OrderStatus::Expired, &0, &10);
+        assert_eq!(ids(&e, &expired), soroban_sdk::vec![&e, ask_id]);
+        assert_eq!(meme_client.balance(&seller), 10_000_000);
+
+        // The sweep picks up where it stopped and wraps around
+        e.ledger().set_timestamp(200);
+        assert_eq!(client.purge_expired(&1), 0);
+        assert_eq!(client.purge_expired(&1), 1);
+        assert_eq!(client.purge_expired(&10), 0);
+        assert_eq!(client.get_order(&bid_id).unwrap().status, OrderStatus::Expired);
+        assert_eq!(client.get_user_orders(&bidder, &OrderStatus::Expired, &0, &10).len(), 1);
+        assert_eq!(client.get_token_orders(&meme).len(), 0);
+        assert_eq!(xlm_client.balance(&bidder), 1_000_000);
+        assert_eq!(xlm_client.balance(&client.address), 0);
+    }
+
+    #[test]
+    fn test_ioc_and_fok_orders_never_rest() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let seller = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &10_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &10_000_000);
+        client.create_sell_order(&seller, &meme, &10_000_000, &2_000_000, &GTC, &0);
+
+        // Fill or kill fails as a whole when the book is too thin
+        assert_eq!(
+            client.try_create_buy_order(&buyer, &meme, &20_000_000, &2_000_000, &FOK, &0),
+            Err(Ok(Error::OrderNotFilled))
+        );
+        assert_eq!(client.get_trade_count(), 0);
+        assert_eq!(xlm_client.balance(&buyer), 10_000_000);
+
+        // Immediate or cancel takes what it can and drops the rest
+        let summary = client.create_buy_order(&buyer, &meme, &20_000_000, &2_000_000, &IOC, &0);
+        assert_eq!(summary.filled, 10_000_000);
+        assert_eq!(summary.order_id, None);
+        assert_eq!(meme_client.balance(&buyer), 10_000_000);
+        assert_eq!(xlm_client.balance(&buyer), 8_000_000);
+        assert_eq!(xlm_client.balance(&client.address), 0);
+        assert_eq!(client.get_token_orders(&meme).len(), 0);
+    }
+
+    #[test]
+    fn test_maker_and_taker_fees_by_volume_tier() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let treasury = Address::generate(&e);
+        let seller = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&seller, &50_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &10_000_000);
+
+        let mut config = FeeConfig {
+            maker_fee_bps: 10,
+            taker_fee_bps: 30,
+            treasury: treasury.clone(),
+            tiers: soroban_sdk::vec![
+                &e,
+                FeeTier { min_volume: 10_000_000, maker_fee_bps: 0, taker_fee_bps: 20 },
+                FeeTier { min_volume: 5_000_000, maker_fee_bps: 0, taker_fee_bps: 10 },
+            ],
+        };
+        assert_eq!(client.try_set_fee_config(&config), Err(Ok(Error::InvalidFeeConfig)));
+        config.tiers.pop_back();
+        client.set_fee_config(&config);
+
+        let order_id = client
+            .create_sell_order(&seller, &meme, &50_000_000, &2_000_000, &GTC, &0)
+            .order_id
+            .unwrap();
+
+        // Makers pay from the XLM they receive, takers from the tokens
+        client.buy_tokens(&buyer, &order_id, &25_000_000);
+        assert_eq!(xlm_client.balance(&seller), 4_995_000);
+        assert_eq!(meme_client.balance(&buyer), 24_925_000);
+        assert_eq!(xlm_client.balance(&buyer), 5_000_000);
+        assert_eq!(client.get_trader_volume(&buyer), 5_000_000);
+
+        let summary = client.market_buy(&buyer, &meme, &25_000_000, &2_000_000);
+        assert_eq!(summary.fee, 75_000);
+        assert_eq!(client.get_accrued_fees(&xlm), 10_000);
+        assert_eq!(client.get_accrued_fees(&meme), 150_000);
+
+        // Both traders have now reached the first tier
+        assert_eq!(client.get_trader_volume(&seller), 10_000_000);
+        assert_eq!(client.get_fee_rates(&seller), (0, 20));
+        assert_eq!(client.get_fee_rates(&Address::generate(&e)), (10, 30));
+
+        assert_eq!(client.claim_fees(&xlm), 10_000);
+        assert_eq!(client.claim_fees(&meme), 150_000);
+        assert_eq!(client.try_claim_fees(&xlm), Err(Ok(Error::NoFeesToClaim)));
+        assert_eq!(xlm_client.balance(&treasury), 10_000);
+        assert_eq!(meme_client.balance(&treasury), 150_000);
+        assert_eq!(xlm_client.balance(&client.address), 0);
+        assert_eq!(meme_client.balance(&client.address), 0);
+    }
+
+    #[test]
+    fn test_best_bid_ask_and_depth_by_price_level() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let trader = Address::generate(&e);
+        StellarAssetClient::new(&e, &xlm).mint(&trader, &1_000_000_000);
+        StellarAssetClient::new(&e, &meme).mint(&trader, &1_000_000_000);
+
+        let empty = client.get_best_bid_ask(&meme);
+        assert_eq!(empty, BestBidAsk { bid: None, ask: None });
+
+        client.create_buy_order(&trader, &meme, &10, &100, &GTC, &0);
+        client.create_buy_order(&trader, &meme, &20, &200, &GTC, &0);
+        client.create_buy_order(&trader, &meme, &30, &100, &GTC, &0);
+        client.create_sell_order(&trader, &meme, &40, &500, &GTC, &0);
+        client.create_sell_order(&trader, &meme, &50, &300, &GTC, &0);
+        client.create_sell_order(&trader, &meme, &60, &300, &GTT, &100);
+
+        let best = client.get_best_bid_ask(&meme);
+        assert_eq!(best, BestBidAsk { bid: Some(200), ask: Some(300) });
+
+        let depth = client.get_depth(&meme, &10);
+        assert_eq!(
+            depth.bids,
+            soroban_sdk::vec![
+                &e,
+                PriceLevel { price: 200, amount: 20, orders: 1 },
+                PriceLevel { price: 100, amount: 40, orders: 2 },
+            ]
+        );
+        assert_eq!(
+            depth.asks,
+            soroban_sdk::vec![
+                &e,
+                PriceLevel { price: 300, amount: 110, orders: 2 },
+                PriceLevel { price: 500, amount: 40, orders: 1 },
+            ]
+        );
+        assert_eq!(client.get_depth(&meme, &1).bids.len(), 1);
+
+        // Expired orders no longer count towards their level
+        e.ledger().set_timestamp(100);
+        assert_eq!(client.get_depth(&meme, &1).asks.get(0).unwrap().amount, 50);
+
+        // Emptied levels leave the index
+        client.market_buy(&trader, &meme, &50, &300);
+        assert_eq!(client.get_best_bid_ask(&meme).ask, Some(500));
+    }
+
+    #[test]
+    fn test_user_orders_paged_by_status() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+
+        let trader = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&trader, &1_000_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&trader, &1_000_000_000);
+
+        let mut order_ids = Vec::new(&e);
+        for price in [100, 200, 300, 400, 500] {
+            let summary = client.create_sell_order(&trader, &meme, &10, &price, &GTC, &0);
+            order_ids.push_back(summary.order_id.unwrap());
+        }
+        client.cancel_order(&trader, &order_ids.get(4).unwrap());
+        client.market_buy(&trader, &meme, &10, &100);
+
+        let open = client.get_user_orders(&trader, &OrderStatus::Open, &0, &2);
+        assert_eq!(ids(&e, &open), order_ids.slice(1..3));
+        let open = client.get_user_orders(&trader, &OrderStatus::Open, &2, &2);
+        assert_eq!(ids(&e, &open), order_ids.slice(3..4));
+        assert_eq!(client.get_user_orders(&trader, &OrderStatus::Open, &3, &2).len(), 0);
+
+        let filled = client.get_user_orders(&trader, &OrderStatus::Filled, &0, &10);
+        assert_eq!(ids(&e, &filled), order_ids.slice(0..1));
+        let cancelled = client.get_user_orders(&trader, &OrderStatus::Cancelled, &0, &10);
+        assert_eq!(ids(&e, &cancelled), order_ids.slice(4..5));
+    }
+
+    #[test]
+    fn test_book_limits_bound_resting_orders_and_fills() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+
+        let maker = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&maker, &1_000_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &1_000_000_000);
+
+        // Dust cannot rest once a minimum value is set
+        client.set_min_order_value(&10_000_000);
+        assert_eq!(
+            client.try_create_sell_order(&maker, &meme, &9, &10_000_000, &GTC, &0),
+            Err(Ok(Error::OrderTooSmall))
+        );
+        client.set_min_order_value(&0);
+
+        for _ in 0..MAX_LEVEL_ORDERS {
+            client.create_sell_order(&maker, &meme, &10, &100, &GTC, &0);
+        }
+        assert_eq!(
+            client.try_create_sell_order(&maker, &meme, &10, &100, &GTC, &0),
+            Err(Ok(Error::PriceLevelFull))
+        );
+        for price in 1..MAX_PRICE_LEVELS {
+            client.create_sell_order(&maker, &meme, &10, &(100 + price as i128), &GTC, &0);
+        }
+        assert_eq!(
+            client.try_create_sell_order(&maker, &meme, &10, &1_000, &GTC, &0),
+            Err(Ok(Error::OrderBookFull))
+        );
+
+        // A taker fills at most MAX_FILLS orders, within one call's budget
+        e.budget().reset_default();
+        let summary = client.market_buy(&buyer, &meme, &1_000, &1_000);
+        assert_eq!(summary.trades, MAX_FILLS);
+        assert_eq!(summary.filled, 10 * MAX_FILLS as i128);
+        assert!(xlm_client.balance(&maker) > 0);
+        assert_eq!(client.get_depth(&meme, &1).asks.get(0).unwrap().price, 101);
+    }
+
+    #[test]
+    fn test_dust_flooded_book_still_takes_competitive_orders() {
+        let e = Env::default();
+        let (client, _, meme) = setup(&e);
+        let meme_client = token::Client::new(&e, &meme);
+
+        let spammer = Address::generate(&e);
+        let maker = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&spammer, &1_000_000);
+        StellarAssetClient::new(&e, &meme).mint(&maker, &1_000_000);
+
+        // Token amounts below the minimum cannot rest, whatever the price
+        client.set_min_order_amount(&100);
+        assert_eq!(
+            client.try_create_sell_order(&spammer, &meme, &99, &i128::MAX, &GTC, &0),
+            Err(Ok(Error::OrderTooSmall))
+        );
+
+        // Fill every ask level with the smallest orders allowed, far from
+        // the market
+        for price in 0..MAX_PRICE_LEVELS {
+            client.create_sell_order(&spammer, &meme, &100, &(1_000 + price as i128), &GTC, &0);
+        }
+        let worst_id = client.get_order_count();
+        assert_eq!(
+            client.try_create_sell_order(&maker, &meme, &100, &2_000, &GTC, &0),
+            Err(Ok(Error::OrderBookFull))
+        );
+
+        // A better price still rests and pushes out the worst level
+        let order_id = client.create_sell_order(&maker, &meme, &100, &500, &GTC, &0).order_id;
+        assert!(order_id.is_some());
+        assert_eq!(client.get_best_bid_ask(&meme).ask, Some(500));
+        assert_eq!(client.get_order(&worst_id).unwrap().status, OrderStatus::Cancelled);
+        assert_eq!(client.get_depth(&meme, &100).asks.len(), MAX_PRICE_LEVELS);
+        assert_eq!(meme_client.balance(&spammer), 1_000_000 - 100 * (MAX_PRICE_LEVELS as i128 - 1));
+    }
+
+    #[test]
+    fn test_orders_cut_short_by_the_fill_cap_do_not_rest() {
+        let e = Env::default();
+        let (client, xlm, meme) = setup(&e);
+        let xlm_client = token::Client::new(&e, &xlm);
+
+        let maker = Address::generate(&e);
+        let buyer = Address::generate(&e);
+        StellarAssetClient::new(&e, &meme).mint(&maker, &1_000_000_000);
+        StellarAssetClient::new(&e, &xlm).mint(&buyer, &1_000_000_000);
+
+        for _ in 0..MAX_FILLS {
+            client.create_sell_order(&maker, &meme, &10, &100, &GTC, &0);
+        }
+        client.create_sell_order(&maker, &meme, &10, &101, &GTC, &0);
+
+        // The last ask still crosses, so the remainder is dropped
+        e.budget().reset_default();
+        let amount = 10 * (MAX_FILLS as i128 + 1);
+        let summary = client.create_buy_order(&buyer, &meme, &amount, &101, &GTC, &0);
+        assert_eq!(summary.trades, MAX_FILLS);
+        assert_eq!(summary.filled, 10 * MAX_FILLS as i128);
+        assert_eq!(summary.order_id, None);
+        assert_eq!(client.get_best_bid_ask(&meme).bid, None);
+        assert_eq!(client.get_best_bid_ask(&meme).ask, Some(101));
+        assert_eq!(xlm_client.balance(&client.address), 0);
     }
 }
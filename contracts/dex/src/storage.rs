@@ -1,17 +1,49 @@
 use soroban_sdk::{contracttype, Address, Env, Vec};
 
-use crate::types::{Order, Trade};
+use crate::errors::Error;
+use crate::fees::FeeConfig;
+use crate::types::{Order, OrderStatus, Side, Trade};
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     OrderCounter,
     TradeCounter,
+    PurgeCursor,
     Order(u64),
     Trade(u64),
     UserOrders(Address),
-    TokenOrders(Address),
+    UserClosedCount(Address, OrderStatus),
+    UserClosed(Address, OrderStatus, u32),
+    PriceLevels(Address, Side),
+    PriceLevel(Address, Side, i128),
     Admin,
+    XlmToken,
+    MinOrderValue,
+    MinOrderAmount,
+    FeeConfig,
+    AccruedFees(Address),
+    TraderVolume(Address),
+}
+
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+fn extend_persistent(e: &Env, key: &DataKey) {
+    e.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
 }
 
 pub fn get_order_counter(e: &Env) -> u64 {
@@ -36,33 +68,45 @@ pub fn set_trade_counter(e: &Env, counter: u64) {
     e.storage().instance().set(&DataKey::TradeCounter, &counter);
 }
 
-pub fn get_order(e: &Env, order_id: u64) -> Option<Order> {
+pub fn get_purge_cursor(e: &Env) -> u64 {
     e.storage()
         .instance()
-        .get(&DataKey::Order(order_id))
+        .get(&DataKey::PurgeCursor)
+        .unwrap_or(0)
 }
 
-pub fn set_order(e: &Env, order_id: u64, order: &Order) {
-    e.storage()
-        .instance()
-        .set(&DataKey::Order(order_id), order);
+pub fn set_purge_cursor(e: &Env, cursor: u64) {
+    e.storage().instance().set(&DataKey::PurgeCursor, &cursor);
+}
+
+pub fn get_order(e: &Env, order_id: u64) -> Option<Order> {
+    let key = DataKey::Order(order_id);
+    let order = e.storage().persistent().get(&key);
+    if order.is_some() {
+        extend_persistent(e, &key);
+    }
+    order
+}
+
+pub fn set_order(e: &Env, order: &Order) {
+    let key = DataKey::Order(order.order_id);
+    e.storage().persistent().set(&key, order);
+    extend_persistent(e, &key);
 }
 
 pub fn get_trade(e: &Env, trade_id: u64) -> Option<Trade> {
-    e.storage()
-        .instance()
-        .get(&DataKey::Trade(trade_id))
+    e.storage().persistent().get(&DataKey::Trade(trade_id))
 }
 
-pub fn set_trade(e: &Env, trade_id: u64, trade: &Trade) {
-    e.storage()
-        .instance()
-        .set(&DataKey::Trade(trade_id), trade);
+pub fn set_trade(e: &Env, trade: &Trade) {
+    let key = DataKey::Trade(trade.trade_id);
+    e.storage().persistent().set(&key, trade);
+    extend_persistent(e, &key);
 }
 
 pub fn get_user_orders(e: &Env, user: &Address) -> Vec<u64> {
     e.storage()
-        .instance()
+        .persistent()
         .get(&DataKey::UserOrders(user.clone()))
         .unwrap_or(Vec::new(e))
 }
@@ -70,37 +114,212 @@ pub fn get_user_orders(e: &Env, user: &Address) -> Vec<u64> {
 pub fn add_user_order(e: &Env, user: &Address, order_id: u64) {
     let mut orders = get_user_orders(e, user);
     orders.push_back(order_id);
+    set_user_orders(e, user, &orders);
+}
+
+pub fn remove_user_order(e: &Env, user: &Address, order_id: u64) {
+    let mut orders = get_user_orders(e, user);
+    if let Some(index) = orders.first_index_of(order_id) {
+        orders.remove(index);
+        set_user_orders(e, user, &orders);
+    }
+}
+
+fn set_user_orders(e: &Env, user: &Address, orders: &Vec<u64>) {
+    let key = DataKey::UserOrders(user.clone());
+    if orders.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, orders);
+        extend_persistent(e, &key);
+    }
+}
+
+pub fn get_closed_count(e: &Env, user: &Address, status: &OrderStatus) -> u32 {
     e.storage()
-        .instance()
-        .set(&DataKey::UserOrders(user.clone()), &orders);
+        .persistent()
+        .get(&DataKey::UserClosedCount(user.clone(), status.clone()))
+        .unwrap_or(0)
 }
 
-pub fn get_token_orders(e: &Env, token: &Address) -> Vec<u64> {
+pub fn add_closed_order(e: &Env, user: &Address, status: &OrderStatus, order_id: u64) {
+    let count = get_closed_count(e, user, status);
+
+    let key = DataKey::UserClosed(user.clone(), status.clone(), count);
+    e.storage().persistent().set(&key, &order_id);
+    extend_persistent(e, &key);
+
+    let key = DataKey::UserClosedCount(user.clone(), status.clone());
+    e.storage().persistent().set(&key, &(count + 1));
+    extend_persistent(e, &key);
+}
+
+pub fn get_closed_order(e: &Env, user: &Address, status: &OrderStatus, index: u32) -> Option<u64> {
     e.storage()
-        .instance()
-        .get(&DataKey::TokenOrders(token.clone()))
+        .persistent()
+        .get(&DataKey::UserClosed(user.clone(), status.clone(), index))
+}
+
+pub fn get_price_levels(e: &Env, token: &Address, side: Side) -> Vec<i128> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::PriceLevels(token.clone(), side))
         .unwrap_or(Vec::new(e))
 }
 
-pub fn add_token_order(e: &Env, token: &Address, order_id: u64) {
-    let mut orders = get_token_orders(e, token);
-    orders.push_back(order_id);
+fn set_price_levels(e: &Env, token: &Address, side: Side, prices: &Vec<i128>) {
+    let key = DataKey::PriceLevels(token.clone(), side);
+    if prices.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, prices);
+        extend_persistent(e, &key);
+    }
+}
+
+pub fn get_level_orders(e: &Env, token: &Address, side: Side, price: i128) -> Vec<u64> {
     e.storage()
-        .instance()
-        .set(&DataKey::TokenOrders(token.clone()), &orders);
+        .persistent()
+        .get(&DataKey::PriceLevel(token.clone(), side, price))
+        .unwrap_or(Vec::new(e))
+}
+
+fn set_level_orders(e: &Env, token: &Address, side: Side, price: i128, orders: &Vec<u64>) {
+    let key = DataKey::PriceLevel(token.clone(), side, price);
+    if orders.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, orders);
+        extend_persistent(e, &key);
+    }
+}
+
+pub fn add_book_order(e: &Env, order: &Order) {
+    let mut orders = get_level_orders(e, &order.token_address, order.side, order.price);
+    if orders.is_empty() {
+        let mut prices = get_price_levels(e, &order.token_address, order.side);
+        let mut index = prices.len();
+        for (i, price) in prices.iter().enumerate() {
+            let better = match order.side {
+                Side::Buy => order.price > price,
+                Side::Sell => order.price < price,
+            };
+            if better {
+                index = i as u32;
+                break;
+            }
+        }
+        prices.insert(index, order.price);
+        set_price_levels(e, &order.token_address, order.side, &prices);
+    }
+
+    orders.push_back(order.order_id);
+    set_level_orders(e, &order.token_address, order.side, order.price, &orders);
+}
+
+pub fn remove_book_order(e: &Env, order: &Order) {
+    let mut orders = get_level_orders(e, &order.token_address, order.side, order.price);
+    let index = match orders.first_index_of(order.order_id) {
+        Some(index) => index,
+        None => return,
+    };
+    orders.remove(index);
+    set_level_orders(e, &order.token_address, order.side, order.price, &orders);
+
+    if orders.is_empty() {
+        let mut prices = get_price_levels(e, &order.token_address, order.side);
+        if let Some(index) = prices.first_index_of(order.price) {
+            prices.remove(index);
+            set_price_levels(e, &order.token_address, order.side, &prices);
+        }
+    }
 }
 
 pub fn has_admin(e: &Env) -> bool {
     e.storage().instance().has(&DataKey::Admin)
 }
 
-pub fn get_admin(e: &Env) -> Address {
+pub fn get_admin(e: &Env) -> Result<Address, Error> {
     e.storage()
         .instance()
         .get(&DataKey::Admin)
-        .expect("Admin not set")
+        .ok_or(Error::NotInitialized)
 }
 
 pub fn set_admin(e: &Env, admin: &Address) {
     e.storage().instance().set(&DataKey::Admin, admin);
 }
+
+pub fn get_xlm_token(e: &Env) -> Result<Address, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::XlmToken)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_xlm_token(e: &Env, xlm_token: &Address) {
+    e.storage().instance().set(&DataKey::XlmToken, xlm_token);
+}
+
+pub fn get_min_order_value(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get(&DataKey::MinOrderValue)
+        .unwrap_or(0)
+}
+
+pub fn set_min_order_value(e: &Env, value: i128) {
+    e.storage().instance().set(&DataKey::MinOrderValue, &value);
+}
+
+pub fn get_min_order_amount(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get(&DataKey::MinOrderAmount)
+        .unwrap_or(0)
+}
+
+pub fn set_min_order_amount(e: &Env, amount: i128) {
+    e.storage().instance().set(&DataKey::MinOrderAmount, &amount);
+}
+
+pub fn get_fee_config(e: &Env) -> Result<FeeConfig, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::FeeConfig)
+        .ok_or(Error::NotInitialized)
+}
+
+pub fn set_fee_config(e: &Env, config: &FeeConfig) {
+    e.storage().instance().set(&DataKey::FeeConfig, config);
+}
+
+pub fn get_accrued_fees(e: &Env, asset: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::AccruedFees(asset.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_accrued_fees(e: &Env, asset: &Address, amount: i128) {
+    let key = DataKey::AccruedFees(asset.clone());
+    if amount == 0 {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, &amount);
+        extend_persistent(e, &key);
+    }
+}
+
+pub fn get_trader_volume(e: &Env, trader: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::TraderVolume(trader.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_trader_volume(e: &Env, trader: &Address, volume: i128) {
+    let key = DataKey::TraderVolume(trader.clone());
+    e.storage().persistent().set(&key, &volume);
+    extend_persistent(e, &key);
+}
@@ -1,26 +1,58 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, Vec};
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum OrderStatus {
     Open,
     Filled,
     Cancelled,
+    Expired,
 }
 
-#[derive(Clone)]
+/// How long an order stays on the book. Good-til-time orders expire at
+/// their `expires_at`, immediate-or-cancel orders drop what they cannot
+/// fill at once and fill-or-kill orders fail unless they fill completely.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TimeInForce {
+    GoodTilCancelled,
+    GoodTilTime,
+    ImmediateOrCancel,
+    FillOrKill,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// A bid or an ask. `amount` is what is left to fill, a bid's `price` is
+/// the most it pays and `expires_at` is zero for orders that never expire.
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct Order {
     pub order_id: u64,
-    pub seller: Address,
+    pub owner: Address,
     pub token_address: Address,
+    pub side: Side,
     pub amount: i128,
-    pub price: i128, // Price in XLM per token
+    pub price: i128, // Price in stroops per whole token
     pub status: OrderStatus,
     pub created_at: u64,
+    pub expires_at: u64,
 }
 
-#[derive(Clone)]
+impl Order {
+    /// Whether the order has expired at ledger timestamp `now`
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// A fill of an order. `total` is the XLM owed for `amount` at `price`.
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct Trade {
     pub trade_id: u64,
@@ -33,3 +65,51 @@ pub struct Trade {
     pub total: i128,
     pub timestamp: u64,
 }
+
+/// Open orders of a token, best price first on each side
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct OrderBook {
+    pub bids: Vec<Order>,
+    pub asks: Vec<Order>,
+}
+
+/// Resting amount at one price of a token's book, summed over `orders`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PriceLevel {
+    pub price: i128,
+    pub amount: i128,
+    pub orders: u32,
+}
+
+/// The best price levels of a token, best price first on each side
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Depth {
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+}
+
+/// Highest bid and lowest ask of a token, `None` while a side is empty
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BestBidAsk {
+    pub bid: Option<i128>,
+    pub ask: Option<i128>,
+}
+
+/// Outcome of an order matched against the book: tokens filled, XLM paid
+/// and the average price over `trades` fills. `fee` is the taker fee, kept
+/// from the tokens or XLM received. `order_id` is the order left resting
+/// with the unfilled remainder, if any.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FillSummary {
+    pub filled: i128,
+    pub total: i128,
+    pub avg_price: i128,
+    pub fee: i128,
+    pub trades: u32,
+    pub order_id: Option<u64>,
+}
@@ -0,0 +1,41 @@
+//! Topics of the events indexers follow across contracts, from launch
+//! through graduation to AMM trading, and of the admin actions that
+//! govern them.
+
+use soroban_sdk::{symbol_short, Symbol};
+
+/// A contract was initialized
+pub const INIT: Symbol = symbol_short!("INIT");
+
+/// The admin role was handed to a new address
+pub const ADMIN: Symbol = symbol_short!("ADMIN");
+
+/// All launches and trading were halted or resumed
+pub const PAUSE: Symbol = symbol_short!("PAUSE");
+
+/// Trading in a single launch was halted or resumed
+pub const TOKEN_PAUSE: Symbol = symbol_short!("TPAUSE");
+
+/// A launch was put into refund mode at a fixed price
+pub const REFUND_MODE: Symbol = symbol_short!("REFUNDON");
+
+/// The graduation thresholds changed
+pub const GRADUATION_CONFIG: Symbol = symbol_short!("GRADCFG");
+
+/// The fee schedule changed
+pub const FEE_CONFIG: Symbol = symbol_short!("FEECFG");
+
+/// A token launch or AMM pool was created
+pub const CREATE: Symbol = symbol_short!("CREATE");
+
+/// Tokens were bought on a bonding curve
+pub const BUY: Symbol = symbol_short!("BUY");
+
+/// Tokens were sold back to a bonding curve
+pub const SELL: Symbol = symbol_short!("SELL");
+
+/// A launch left its bonding curve for an AMM pool
+pub const GRADUATE: Symbol = symbol_short!("GRADUATE");
+
+/// Tokens were swapped in an AMM pool
+pub const SWAP: Symbol = symbol_short!("SWAP");
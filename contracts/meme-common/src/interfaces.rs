@@ -0,0 +1,20 @@
+//! Clients for calling one meme-coin contract from another
+
+use soroban_sdk::{contractclient, Address, Env, String};
+
+/// Interface of the `amm` contract, used to seed a pool on graduation
+#[allow(dead_code)]
+#[contractclient(name = "AmmClient")]
+pub trait AmmInterface {
+    #[allow(clippy::too_many_arguments)]
+    fn create_pool(
+        env: Env,
+        creator: Address,
+        token_address: Address,
+        name: String,
+        symbol: String,
+        total_supply: i128,
+        token_amount: i128,
+        initial_xlm: i128,
+    ) -> u64;
+}
@@ -0,0 +1,12 @@
+#![no_std]
+
+//! Types shared by the meme-coin contracts: fixed-point math, token info,
+//! event topics and clients for calling one contract from another.
+
+pub mod events;
+pub mod interfaces;
+pub mod math;
+mod types;
+
+pub use math::MathError;
+pub use types::TokenInfo;
@@ -0,0 +1,151 @@
+//! Overflow-safe fixed-point helpers.
+//!
+//! Products of two `i128` values are carried in a 256-bit intermediate so
+//! reserves and supplies of any realistic size can be multiplied before
+//! dividing. Every rounding direction is explicit so callers can always
+//! round in the protocol's favor.
+
+use soroban_sdk::{contracterror, Env, U256};
+
+/// Prices are quoted in stroops per whole token (7 decimals)
+pub const PRICE_SCALE: i128 = 10_000_000;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum MathError {
+    Overflow = 100,
+    DivisionByZero = 101,
+    NegativeValue = 102,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+pub fn add(a: i128, b: i128) -> Result<i128, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+pub fn sub(a: i128, b: i128) -> Result<i128, MathError> {
+    a.checked_sub(b).ok_or(MathError::Overflow)
+}
+
+pub fn mul(a: i128, b: i128) -> Result<i128, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// `a * b / denominator` for non-negative operands
+pub fn mul_div(
+    e: &Env,
+    a: i128,
+    b: i128,
+    denominator: i128,
+    rounding: Rounding,
+) -> Result<i128, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+
+    let product = to_u256(e, a)?.mul(&to_u256(e, b)?);
+    let denominator = to_u256(e, denominator)?;
+
+    let mut quotient = product.div(&denominator);
+    if rounding == Rounding::Up && product.rem_euclid(&denominator) != U256::from_u32(e, 0) {
+        quotient = quotient.add(&U256::from_u32(e, 1));
+    }
+
+    from_u256(&quotient)
+}
+
+/// `floor(sqrt(a * b))` for non-negative operands
+pub fn sqrt_mul(e: &Env, a: i128, b: i128) -> Result<i128, MathError> {
+    let x = to_u256(e, a)?.mul(&to_u256(e, b)?);
+    let zero = U256::from_u32(e, 0);
+    if x == zero {
+        return Ok(0);
+    }
+
+    // Newton's method starting above the root, converges downwards
+    let one = U256::from_u32(e, 1);
+    let two = U256::from_u32(e, 2);
+    let mut y = x.clone();
+    let mut z = x.add(&one).div(&two);
+    while z < y {
+        y = z.clone();
+        z = x.div(&z).add(&z).div(&two);
+    }
+
+    from_u256(&y)
+}
+
+fn to_u256(e: &Env, value: i128) -> Result<U256, MathError> {
+    if value < 0 {
+        return Err(MathError::NegativeValue);
+    }
+    Ok(U256::from_u128(e, value as u128))
+}
+
+fn from_u256(value: &U256) -> Result<i128, MathError> {
+    value
+        .to_u128()
+        .and_then(|v| i128::try_from(v).ok())
+        .ok_or(MathError::Overflow)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mul_div_rounds_both_ways() {
+        let e = Env::default();
+        assert_eq!(mul_div(&e, 10, 10, 3, Rounding::Down), Ok(33));
+        assert_eq!(mul_div(&e, 10, 10, 3, Rounding::Up), Ok(34));
+        assert_eq!(mul_div(&e, 9, 10, 3, Rounding::Up), Ok(30));
+    }
+
+    #[test]
+    fn test_mul_div_wide_intermediate() {
+        let e = Env::default();
+        // 1e15 tokens with 7 decimals squared overflows i128 on its own
+        let supply = 10_000_000_000_000_000_000_000i128;
+        assert_eq!(mul_div(&e, supply, supply, supply, Rounding::Down), Ok(supply));
+        assert_eq!(
+            mul_div(&e, i128::MAX, 2, 1, Rounding::Down),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_rejects_bad_input() {
+        let e = Env::default();
+        assert_eq!(mul_div(&e, 1, 1, 0, Rounding::Down), Err(MathError::DivisionByZero));
+        assert_eq!(mul_div(&e, -1, 1, 1, Rounding::Down), Err(MathError::NegativeValue));
+    }
+
+    #[test]
+    fn test_sqrt_mul() {
+        let e = Env::default();
+        assert_eq!(sqrt_mul(&e, 0, 5), Ok(0));
+        assert_eq!(sqrt_mul(&e, 4, 4), Ok(4));
+        assert_eq!(sqrt_mul(&e, 2, 5), Ok(3));
+
+        let supply = 10_000_000_000_000_000_000_000i128;
+        assert_eq!(sqrt_mul(&e, supply, supply), Ok(supply));
+    }
+
+    #[test]
+    fn test_sqrt_mul_rounds_down() {
+        let e = Env::default();
+        for (x, root) in [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (8, 2), (9, 3)] {
+            assert_eq!(sqrt_mul(&e, x, 1), Ok(root));
+        }
+
+        assert_eq!(sqrt_mul(&e, i128::MAX, 1), Ok(13_043_817_825_332_782_212));
+        assert_eq!(sqrt_mul(&e, i128::MAX, i128::MAX), Ok(i128::MAX));
+        assert_eq!(sqrt_mul(&e, i128::MAX, i128::MAX - 1), Ok(i128::MAX - 1));
+    }
+}
@@ -0,0 +1,15 @@
+use soroban_sdk::{contracttype, Address, String};
+
+/// A meme token as listed by the factory
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TokenInfo {
+    pub token_id: u32,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+    pub total_supply: i128,
+    pub creator: Address,
+    pub contract_address: Address,
+    pub created_at: u64,
+}
@@ -25,25 +25,25 @@ fi
 
 # 1. Install Token WASM
 echo ""
-echo "📤 [1/7] Installing Token WASM..."
+echo "📤 [1/9] Installing Token WASM..."
 TOKEN_WASM_HASH=$(stellar contract install \
-  --wasm token/target/wasm32-unknown-unknown/release/token.wasm \
+  --wasm target/wasm32-unknown-unknown/release/token.wasm \
   --source deployer \
   --network testnet)
 echo "✅ Token WASM: $TOKEN_WASM_HASH"
 
 # 2. Deploy Token Factory
 echo ""
-echo "📤 [2/7] Deploying Token Factory..."
+echo "📤 [2/9] Deploying Token Factory..."
 FACTORY_ID=$(stellar contract deploy \
-  --wasm token-factory/target/wasm32-unknown-unknown/release/token_factory.wasm \
+  --wasm target/wasm32-unknown-unknown/release/token_factory.wasm \
   --source deployer \
   --network testnet)
 echo "✅ Factory ID: $FACTORY_ID"
 
 # 3. Initialize Factory
 echo ""
-echo "🔧 [3/7] Initializing Factory..."
+echo "🔧 [3/9] Initializing Factory..."
 stellar contract invoke \
   --id $FACTORY_ID \
   --source deployer \
@@ -55,27 +55,60 @@ echo "✅ Factory Initialized"
 
 # 4. Deploy DEX
 echo ""
-echo "📤 [4/7] Deploying DEX..."
+echo "📤 [4/9] Deploying DEX..."
 DEX_ID=$(stellar contract deploy \
-  --wasm dex/target/wasm32-unknown-unknown/release/dex.wasm \
+  --wasm target/wasm32-unknown-unknown/release/dex.wasm \
   --source deployer \
   --network testnet)
 echo "✅ DEX ID: $DEX_ID"
 
 # 5. Initialize DEX
 echo ""
-echo "🔧 [5/7] Initializing DEX..."
+echo "🔧 [5/9] Initializing DEX..."
 stellar contract invoke \
   --id $DEX_ID \
   --source deployer \
   --network testnet \
   -- initialize \
-  --admin $(stellar keys address deployer)
+  --admin $(stellar keys address deployer) \
+  --xlm_token $(stellar contract id asset --asset native --network testnet)
 echo "✅ DEX Initialized"
 
-# 6. Create Test Token
+# 6. Deploy and Initialize AMM
+echo ""
+echo "📤 [6/9] Deploying AMM..."
+AMM_ID=$(stellar contract deploy \
+  --wasm target/wasm32-unknown-unknown/release/amm.wasm \
+  --source deployer \
+  --network testnet)
+stellar contract invoke \
+  --id $AMM_ID \
+  --source deployer \
+  --network testnet \
+  -- initialize \
+  --xlm_token $(stellar contract id asset --asset native --network testnet)
+echo "✅ AMM ID: $AMM_ID"
+
+# 7. Deploy and Initialize Bonding Curve
+echo ""
+echo "📤 [7/9] Deploying Bonding Curve..."
+CURVE_ID=$(stellar contract deploy \
+  --wasm target/wasm32-unknown-unknown/release/bonding_curve.wasm \
+  --source deployer \
+  --network testnet)
+stellar contract invoke \
+  --id $CURVE_ID \
+  --source deployer \
+  --network testnet \
+  -- initialize \
+  --admin $(stellar keys address deployer) \
+  --xlm_token $(stellar contract id asset --asset native --network testnet) \
+  --amm $AMM_ID
+echo "✅ Bonding Curve ID: $CURVE_ID"
+
+# 8. Create Test Token
 echo ""
-echo "🧪 [6/7] Creating Test Token..."
+echo "🧪 [8/9] Creating Test Token..."
 TEST_TOKEN=$(stellar contract invoke \
   --id $FACTORY_ID \
   --source deployer \
@@ -88,9 +121,9 @@ TEST_TOKEN=$(stellar contract invoke \
   --initial_supply 1000000000000 2>&1 | grep -o 'C[A-Z0-9]\{55\}' | head -1)
 echo "✅ Test Token: $TEST_TOKEN"
 
-# 7. Verify
+# 9. Verify
 echo ""
-echo "✅ [7/7] Verifying..."
+echo "✅ [9/9] Verifying..."
 TOKEN_COUNT=$(stellar contract invoke \
   --id $FACTORY_ID \
   --source deployer \
@@ -113,6 +146,12 @@ echo ""
 echo "DEX Contract:"
 echo "$DEX_ID"
 echo ""
+echo "AMM Contract:"
+echo "$AMM_ID"
+echo ""
+echo "Bonding Curve Contract:"
+echo "$CURVE_ID"
+echo ""
 echo "Test Token Address:"
 echo "$TEST_TOKEN"
 echo ""
@@ -124,6 +163,8 @@ echo "Frontend .env Configuration:"
 echo "=========================================="
 echo "VITE_TOKEN_FACTORY_CONTRACT_ID=$FACTORY_ID"
 echo "VITE_DEX_CONTRACT_ID=$DEX_ID"
+echo "VITE_AMM_CONTRACT_ID=$AMM_ID"
+echo "VITE_BONDING_CURVE_CONTRACT_ID=$CURVE_ID"
 echo ""
 
 # Save to file
@@ -136,12 +177,16 @@ Network: Stellar Testnet
 Token WASM Hash: $TOKEN_WASM_HASH
 Token Factory: $FACTORY_ID
 DEX Contract: $DEX_ID
+AMM Contract: $AMM_ID
+Bonding Curve: $CURVE_ID
 Test Token: $TEST_TOKEN
 Admin: $(stellar keys address deployer)
 
 Frontend .env:
 VITE_TOKEN_FACTORY_CONTRACT_ID=$FACTORY_ID
 VITE_DEX_CONTRACT_ID=$DEX_ID
+VITE_AMM_CONTRACT_ID=$AMM_ID
+VITE_BONDING_CURVE_CONTRACT_ID=$CURVE_ID
 EOF
 
 echo "📝 Saved to deployment-info.txt"
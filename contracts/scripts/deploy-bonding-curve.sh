@@ -0,0 +1,80 @@
+#!/bin/bash
+
+set -e
+
+# Configuration
+NETWORK="testnet"
+RPC_URL="https://soroban-testnet.stellar.org"
+NETWORK_PASSPHRASE="Test SDF Network ; September 2015"
+
+# Check if secret key is provided
+if [ -z "$STELLAR_SECRET_KEY" ]; then
+    echo "❌ Error: STELLAR_SECRET_KEY environment variable not set"
+    echo "Usage: export STELLAR_SECRET_KEY=your_secret_key"
+    exit 1
+fi
+
+echo "🚀 Deploying AMM and Bonding Curve Contracts..."
+
+# Get admin address from secret key
+ADMIN_ADDRESS=$(soroban keys address $STELLAR_SECRET_KEY)
+
+# Native XLM Stellar Asset Contract that trades settle in
+XLM_TOKEN=$(soroban contract id asset \
+  --asset native \
+  --rpc-url $RPC_URL \
+  --network-passphrase "$NETWORK_PASSPHRASE")
+
+# Step 1: Deploy and initialize the AMM that launches graduate to
+echo "📤 Deploying AMM contract..."
+AMM_CONTRACT_ID=$(soroban contract deploy \
+  --wasm ../target/wasm32-unknown-unknown/release/amm.wasm \
+  --source $STELLAR_SECRET_KEY \
+  --rpc-url $RPC_URL \
+  --network-passphrase "$NETWORK_PASSPHRASE")
+
+echo "✅ AMM deployed: $AMM_CONTRACT_ID"
+
+echo "🔧 Initializing AMM..."
+soroban contract invoke \
+  --id $AMM_CONTRACT_ID \
+  --source $STELLAR_SECRET_KEY \
+  --rpc-url $RPC_URL \
+  --network-passphrase "$NETWORK_PASSPHRASE" \
+  -- initialize \
+  --xlm_token $XLM_TOKEN
+
+# Step 2: Deploy and initialize the bonding curve
+echo "📤 Deploying bonding curve contract..."
+CURVE_CONTRACT_ID=$(soroban contract deploy \
+  --wasm ../target/wasm32-unknown-unknown/release/bonding_curve.wasm \
+  --source $STELLAR_SECRET_KEY \
+  --rpc-url $RPC_URL \
+  --network-passphrase "$NETWORK_PASSPHRASE")
+
+echo "✅ Bonding curve deployed: $CURVE_CONTRACT_ID"
+
+echo "🔧 Initializing bonding curve..."
+soroban contract invoke \
+  --id $CURVE_CONTRACT_ID \
+  --source $STELLAR_SECRET_KEY \
+  --rpc-url $RPC_URL \
+  --network-passphrase "$NETWORK_PASSPHRASE" \
+  -- initialize \
+  --admin $ADMIN_ADDRESS \
+  --xlm_token $XLM_TOKEN \
+  --amm $AMM_CONTRACT_ID
+
+echo ""
+echo "🎉 Deployment Complete!"
+echo ""
+echo "======================================"
+echo "Contract Details:"
+echo "======================================"
+echo "AMM Contract ID: $AMM_CONTRACT_ID"
+echo "Bonding Curve Contract ID: $CURVE_CONTRACT_ID"
+echo "Admin Address: $ADMIN_ADDRESS"
+echo ""
+echo "Add these to your .env file:"
+echo "VITE_AMM_CONTRACT_ID=$AMM_CONTRACT_ID"
+echo "VITE_BONDING_CURVE_CONTRACT_ID=$CURVE_CONTRACT_ID"
@@ -19,7 +19,7 @@ echo "🚀 Deploying DEX Contract..."
 # Deploy DEX contract
 echo "📤 Deploying DEX contract..."
 DEX_CONTRACT_ID=$(soroban contract deploy \
-  --wasm ../dex/target/wasm32-unknown-unknown/release/dex.wasm \
+  --wasm ../target/wasm32-unknown-unknown/release/dex.wasm \
   --source $STELLAR_SECRET_KEY \
   --rpc-url $RPC_URL \
   --network-passphrase "$NETWORK_PASSPHRASE")
@@ -29,6 +29,12 @@ echo "✅ DEX deployed: $DEX_CONTRACT_ID"
 # Get admin address from secret key
 ADMIN_ADDRESS=$(soroban keys address $STELLAR_SECRET_KEY)
 
+# Native XLM Stellar Asset Contract that fills are paid in
+XLM_TOKEN=$(soroban contract id asset \
+  --asset native \
+  --rpc-url $RPC_URL \
+  --network-passphrase "$NETWORK_PASSPHRASE")
+
 # Initialize the DEX
 echo "🔧 Initializing DEX..."
 soroban contract invoke \
@@ -37,7 +43,8 @@ soroban contract invoke \
   --rpc-url $RPC_URL \
   --network-passphrase "$NETWORK_PASSPHRASE" \
   -- initialize \
-  --admin $ADMIN_ADDRESS
+  --admin $ADMIN_ADDRESS \
+  --xlm_token $XLM_TOKEN
 
 echo ""
 echo "🎉 Deployment Complete!"
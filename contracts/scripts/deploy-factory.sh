@@ -19,7 +19,7 @@ echo "🚀 Deploying Token Factory Contract..."
 # Step 1: Upload token WASM and get hash
 echo "📤 Uploading token contract WASM..."
 TOKEN_WASM_HASH=$(soroban contract install \
-  --wasm ../token/target/wasm32-unknown-unknown/release/token.wasm \
+  --wasm ../target/wasm32-unknown-unknown/release/token.wasm \
   --source $STELLAR_SECRET_KEY \
   --rpc-url $RPC_URL \
   --network-passphrase "$NETWORK_PASSPHRASE")
@@ -29,7 +29,7 @@ echo "✅ Token WASM uploaded: $TOKEN_WASM_HASH"
 # Step 2: Deploy token factory contract
 echo "📤 Deploying token factory contract..."
 FACTORY_CONTRACT_ID=$(soroban contract deploy \
-  --wasm ../token-factory/target/wasm32-unknown-unknown/release/token_factory.wasm \
+  --wasm ../target/wasm32-unknown-unknown/release/token_factory.wasm \
   --source $STELLAR_SECRET_KEY \
   --rpc-url $RPC_URL \
   --network-passphrase "$NETWORK_PASSPHRASE")
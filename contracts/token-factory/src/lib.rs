@@ -1,5 +1,6 @@
 #![no_std]
 
+mod errors;
 mod storage;
 mod types;
 
@@ -7,8 +8,9 @@ use soroban_sdk::{
     contract, contractimpl, Address, Env, String, Symbol, Vec, Bytes, BytesN, Val, IntoVal,
 };
 
+pub use errors::Error;
+pub use meme_common::TokenInfo;
 use storage::*;
-use types::*;
 
 #[contract]
 pub struct TokenFactory;
@@ -16,14 +18,16 @@ pub struct TokenFactory;
 #[contractimpl]
 impl TokenFactory {
     /// Initialize the token factory
-    pub fn initialize(e: Env, admin: Address, token_wasm_hash: BytesN<32>) {
+    pub fn initialize(e: Env, admin: Address, token_wasm_hash: BytesN<32>) -> Result<(), Error> {
         if has_admin(&e) {
-            panic!("Already initialized");
+            return Err(Error::AlreadyInitialized);
         }
 
         set_admin(&e, &admin);
         set_token_wasm_hash(&e, &token_wasm_hash);
         set_token_counter(&e, 0);
+
+        Ok(())
     }
 
     /// Create a new token
@@ -34,15 +38,15 @@ impl TokenFactory {
         symbol: String,
         decimals: u32,
         initial_supply: i128,
-    ) -> Address {
+    ) -> Result<Address, Error> {
         creator.require_auth();
 
         // Validate inputs
         if decimals > 18 {
-            panic!("Decimals cannot exceed 18");
+            return Err(Error::InvalidDecimals);
         }
         if initial_supply <= 0 {
-            panic!("Initial supply must be positive");
+            return Err(Error::InvalidSupply);
         }
 
         // Get current counter and increment
@@ -61,7 +65,7 @@ impl TokenFactory {
         ));
 
         // Deploy token contract
-        let token_wasm_hash = get_token_wasm_hash(&e);
+        let token_wasm_hash = get_token_wasm_hash(&e)?;
         let deployed_address = e
             .deployer()
             .with_current_contract(salt)
@@ -107,7 +111,7 @@ impl TokenFactory {
             counter,
         );
 
-        deployed_address
+        Ok(deployed_address)
     }
 
     /// Get token information by ID
@@ -140,14 +144,16 @@ impl TokenFactory {
     }
 
     /// Update token WASM hash (admin only)
-    pub fn update_token_wasm(e: Env, new_hash: BytesN<32>) {
-        let admin = get_admin(&e);
+    pub fn update_token_wasm(e: Env, new_hash: BytesN<32>) -> Result<(), Error> {
+        let admin = get_admin(&e)?;
         admin.require_auth();
         set_token_wasm_hash(&e, &new_hash);
+
+        Ok(())
     }
 
     /// Get admin address
-    pub fn get_admin(e: Env) -> Address {
+    pub fn get_admin(e: Env) -> Result<Address, Error> {
         get_admin(&e)
     }
 }
@@ -168,5 +174,9 @@ mod test {
 
         client.initialize(&admin, &token_wasm_hash);
         assert_eq!(client.get_token_count(), 0);
+        assert_eq!(
+            client.try_initialize(&admin, &token_wasm_hash),
+            Err(Ok(Error::AlreadyInitialized))
+        );
     }
 }
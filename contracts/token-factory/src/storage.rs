@@ -1,6 +1,7 @@
 use soroban_sdk::{contracttype, Address, Env, Vec};
 
-use crate::types::TokenInfo;
+use crate::errors::Error;
+use meme_common::TokenInfo;
 
 #[derive(Clone)]
 #[contracttype]
@@ -50,11 +51,11 @@ pub fn add_creator_token(e: &Env, creator: &Address, token_id: u32) {
         .set(&DataKey::CreatorTokens(creator.clone()), &tokens);
 }
 
-pub fn get_token_wasm_hash(e: &Env) -> soroban_sdk::BytesN<32> {
+pub fn get_token_wasm_hash(e: &Env) -> Result<soroban_sdk::BytesN<32>, Error> {
     e.storage()
         .instance()
         .get(&DataKey::TokenWasmHash)
-        .expect("Token WASM hash not set")
+        .ok_or(Error::NotInitialized)
 }
 
 pub fn set_token_wasm_hash(e: &Env, hash: &soroban_sdk::BytesN<32>) {
@@ -65,11 +66,11 @@ pub fn has_admin(e: &Env) -> bool {
     e.storage().instance().has(&DataKey::Admin)
 }
 
-pub fn get_admin(e: &Env) -> Address {
+pub fn get_admin(e: &Env) -> Result<Address, Error> {
     e.storage()
         .instance()
         .get(&DataKey::Admin)
-        .expect("Admin not set")
+        .ok_or(Error::NotInitialized)
 }
 
 pub fn set_admin(e: &Env, admin: &Address) {
@@ -1,9 +1,13 @@
 #![no_std]
 
+mod errors;
+
 use soroban_sdk::{
     contract, contractimpl, contracttype, Address, Env, String,
 };
 
+pub use errors::Error;
+
 #[derive(Clone)]
 #[contracttype]
 pub struct AllowanceDataKey {
@@ -27,18 +31,22 @@ fn has_administrator(e: &Env) -> bool {
     e.storage().instance().has(&DataKey::Admin)
 }
 
-fn read_administrator(e: &Env) -> Address {
-    e.storage().instance().get(&DataKey::Admin).unwrap()
+fn read_administrator(e: &Env) -> Result<Address, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
 }
 
 fn write_administrator(e: &Env, id: &Address) {
     e.storage().instance().set(&DataKey::Admin, id);
 }
 
-pub fn check_nonnegative_amount(amount: i128) {
+pub fn check_nonnegative_amount(amount: i128) -> Result<(), Error> {
     if amount < 0 {
-        panic!("negative amount is not allowed: {}", amount)
+        return Err(Error::NegativeAmount);
     }
+    Ok(())
 }
 
 fn read_balance(e: &Env, addr: Address) -> i128 {
@@ -54,12 +62,13 @@ fn write_balance(e: &Env, addr: Address, amount: i128) {
     e.storage().persistent().set(&key, &amount);
 }
 
-fn spend_balance(e: &Env, addr: Address, amount: i128) {
+fn spend_balance(e: &Env, addr: Address, amount: i128) -> Result<(), Error> {
     let balance = read_balance(e, addr.clone());
     if balance < amount {
-        panic!("insufficient balance");
+        return Err(Error::InsufficientBalance);
     }
     write_balance(e, addr, balance - amount);
+    Ok(())
 }
 
 fn receive_balance(e: &Env, addr: Address, amount: i128) {
@@ -72,27 +81,35 @@ pub struct Token;
 
 #[contractimpl]
 impl Token {
-    pub fn initialize(e: Env, admin: Address, decimal: u32, name: String, symbol: String) {
+    pub fn initialize(
+        e: Env,
+        admin: Address,
+        decimal: u32,
+        name: String,
+        symbol: String,
+    ) -> Result<(), Error> {
         if has_administrator(&e) {
-            panic!("already initialized");
+            return Err(Error::AlreadyInitialized);
         }
 
-        write_administrator(&e, &admin);
-
         if decimal > 18 {
-            panic!("Decimal must not be greater than 18");
+            return Err(Error::InvalidDecimals);
         }
 
+        write_administrator(&e, &admin);
+
         // Store metadata directly
         e.storage().instance().set(&DataKey::Decimals, &decimal);
         e.storage().instance().set(&DataKey::Name, &name);
         e.storage().instance().set(&DataKey::Symbol, &symbol);
         e.storage().instance().set(&DataKey::TotalSupply, &0i128);
+
+        Ok(())
     }
 
-    pub fn mint(e: Env, to: Address, amount: i128) {
-        check_nonnegative_amount(amount);
-        let admin = read_administrator(&e);
+    pub fn mint(e: Env, to: Address, amount: i128) -> Result<(), Error> {
+        check_nonnegative_amount(amount)?;
+        let admin = read_administrator(&e)?;
         admin.require_auth();
 
         receive_balance(&e, to.clone(), amount);
@@ -109,6 +126,8 @@ impl Token {
         // Emit event
         e.events()
             .publish(("mint", admin), (to, amount));
+
+        Ok(())
     }
 
     pub fn allowance(e: Env, from: Address, spender: Address) -> i128 {
@@ -119,9 +138,15 @@ impl Token {
             .unwrap_or(0)
     }
 
-    pub fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+    pub fn approve(
+        e: Env,
+        from: Address,
+        spender: Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) -> Result<(), Error> {
         from.require_auth();
-        check_nonnegative_amount(amount);
+        check_nonnegative_amount(amount)?;
 
         let key = DataKey::Allowance(AllowanceDataKey {
             from: from.clone(),
@@ -133,40 +158,50 @@ impl Token {
         if amount > 0 {
             let live_for = expiration_ledger
                 .checked_sub(e.ledger().sequence())
-                .expect("expiration must be in the future");
+                .ok_or(Error::InvalidExpiration)?;
 
             e.storage().temporary().extend_ttl(&key, live_for, live_for);
         }
 
         e.events()
             .publish(("approve", from), (spender, amount, expiration_ledger));
+
+        Ok(())
     }
 
     pub fn balance(e: Env, id: Address) -> i128 {
         read_balance(&e, id)
     }
 
-    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) -> Result<(), Error> {
         from.require_auth();
-        check_nonnegative_amount(amount);
+        check_nonnegative_amount(amount)?;
 
-        spend_balance(&e, from.clone(), amount);
+        spend_balance(&e, from.clone(), amount)?;
         receive_balance(&e, to.clone(), amount);
 
         e.events()
             .publish(("transfer", from), (to, amount));
+
+        Ok(())
     }
 
-    pub fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+    pub fn transfer_from(
+        e: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
         spender.require_auth();
-        check_nonnegative_amount(amount);
+        check_nonnegative_amount(amount)?;
 
         let allowance = Self::allowance(e.clone(), from.clone(), spender.clone());
         if allowance < amount {
-            panic!("insufficient allowance");
+            return Err(Error::InsufficientAllowance);
         }
 
-        spend_balance(&e, from.clone(), amount);
+        spend_balance(&e, from.clone(), amount)?;
         receive_balance(&e, to.clone(), amount);
 
         let new_allowance = allowance - amount;
@@ -178,13 +213,15 @@ impl Token {
 
         e.events()
             .publish(("transfer", from), (to, amount));
+
+        Ok(())
     }
 
-    pub fn burn(e: Env, from: Address, amount: i128) {
+    pub fn burn(e: Env, from: Address, amount: i128) -> Result<(), Error> {
         from.require_auth();
-        check_nonnegative_amount(amount);
+        check_nonnegative_amount(amount)?;
 
-        spend_balance(&e, from.clone(), amount);
+        spend_balance(&e, from.clone(), amount)?;
         
         // Update total supply
         let total: i128 = e.storage()
@@ -197,18 +234,20 @@ impl Token {
 
         e.events()
             .publish(("burn", from.clone()), amount);
+
+        Ok(())
     }
 
-    pub fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+    pub fn burn_from(e: Env, spender: Address, from: Address, amount: i128) -> Result<(), Error> {
         spender.require_auth();
-        check_nonnegative_amount(amount);
+        check_nonnegative_amount(amount)?;
 
         let allowance = Self::allowance(e.clone(), from.clone(), spender.clone());
         if allowance < amount {
-            panic!("insufficient allowance");
+            return Err(Error::InsufficientAllowance);
         }
 
-        spend_balance(&e, from.clone(), amount);
+        spend_balance(&e, from.clone(), amount)?;
 
         let new_allowance = allowance - amount;
         let key = DataKey::Allowance(AllowanceDataKey {
@@ -228,6 +267,8 @@ impl Token {
 
         e.events()
             .publish(("burn", from.clone()), amount);
+
+        Ok(())
     }
 
     pub fn decimals(e: Env) -> u32 {